@@ -0,0 +1,2 @@
+ALTER TABLE slot DROP COLUMN min_staff;
+ALTER TABLE slot DROP COLUMN max_staff;
@@ -0,0 +1,2 @@
+ALTER TABLE slot ADD min_staff INT CHECK (min_staff >= 0);
+ALTER TABLE slot ADD max_staff INT CHECK (max_staff >= 0);
@@ -0,0 +1,15 @@
+CREATE TABLE schedule_candidate
+(
+    schedule_id    UUID REFERENCES schedule (id) NOT NULL,
+    slot_id        UUID REFERENCES slot (id)     NOT NULL,
+    subject_id     UUID REFERENCES subject (id)  NOT NULL,
+
+    weeks_since    BIGINT,
+    flexibility    INT              NOT NULL,
+    total_shifted  INT              NOT NULL,
+    shifts_current INT              NOT NULL,
+    weight         DOUBLE PRECISION NOT NULL,
+    chosen         BOOLEAN          NOT NULL,
+
+    PRIMARY KEY (schedule_id, slot_id, subject_id)
+);
@@ -0,0 +1,2 @@
+ALTER TABLE schedule_candidate DROP COLUMN excluded;
+ALTER TABLE subject DROP COLUMN max_shifts_per_week;
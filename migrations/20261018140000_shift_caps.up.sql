@@ -0,0 +1,2 @@
+ALTER TABLE subject ADD max_shifts_per_week INT CHECK (max_shifts_per_week >= 0);
+ALTER TABLE schedule_candidate ADD excluded TEXT;
@@ -0,0 +1 @@
+DROP TABLE pairing_rule;
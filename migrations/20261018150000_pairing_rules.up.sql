@@ -0,0 +1,10 @@
+CREATE TABLE pairing_rule
+(
+    id         UUID PRIMARY KEY NOT NULL,
+    kind       TEXT NOT NULL CHECK (kind IN ('must_pair', 'never_pair')),
+    subject_id UUID REFERENCES subject (id) NOT NULL,
+    partner_id UUID REFERENCES subject (id) NOT NULL,
+
+    CHECK (subject_id <> partner_id),
+    UNIQUE (kind, subject_id, partner_id)
+);
@@ -0,0 +1,3 @@
+DROP TABLE slot_role_requirement;
+DROP TABLE subject_role;
+DROP TABLE role;
@@ -0,0 +1,22 @@
+CREATE TABLE role
+(
+    id   UUID PRIMARY KEY NOT NULL,
+    name TEXT UNIQUE NOT NULL
+);
+
+CREATE TABLE subject_role
+(
+    subject_id UUID REFERENCES subject (id)                 NOT NULL,
+    role_id    UUID REFERENCES role (id) ON DELETE CASCADE NOT NULL,
+
+    PRIMARY KEY (subject_id, role_id)
+);
+
+CREATE TABLE slot_role_requirement
+(
+    slot_id UUID REFERENCES slot (id)                 NOT NULL,
+    role_id UUID REFERENCES role (id) ON DELETE CASCADE NOT NULL,
+    count   INT NOT NULL CHECK (count >= 1),
+
+    PRIMARY KEY (slot_id, role_id)
+);
@@ -0,0 +1,8 @@
+CREATE TABLE schedule_pin
+(
+    schedule_id UUID REFERENCES schedule (id) NOT NULL,
+    slot_id     UUID REFERENCES slot (id)     NOT NULL,
+    subject_id  UUID REFERENCES subject (id)  NOT NULL,
+
+    PRIMARY KEY (schedule_id, slot_id, subject_id)
+);
@@ -0,0 +1 @@
+ALTER TABLE schedule ADD revision BOOLEAN NOT NULL DEFAULT FALSE;
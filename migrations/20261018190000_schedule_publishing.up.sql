@@ -0,0 +1 @@
+ALTER TABLE schedule ADD published_at TIMESTAMPTZ DEFAULT NULL;
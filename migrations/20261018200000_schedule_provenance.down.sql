@@ -0,0 +1,6 @@
+ALTER TABLE schedule
+    DROP COLUMN availability_id,
+    DROP COLUMN strategy,
+    DROP COLUMN parameters,
+    DROP COLUMN algorithm_version,
+    DROP COLUMN created_at;
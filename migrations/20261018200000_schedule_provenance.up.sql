@@ -0,0 +1,6 @@
+ALTER TABLE schedule
+    ADD availability_id   UUID        REFERENCES availability (id) DEFAULT NULL,
+    ADD strategy          TEXT        DEFAULT NULL,
+    ADD parameters        JSONB       DEFAULT NULL,
+    ADD algorithm_version INT         DEFAULT NULL,
+    ADD created_at        TIMESTAMPTZ NOT NULL DEFAULT now();
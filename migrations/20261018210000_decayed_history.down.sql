@@ -0,0 +1 @@
+ALTER TABLE schedule_candidate ALTER COLUMN total_shifted TYPE INT USING round(total_shifted);
@@ -0,0 +1 @@
+ALTER TABLE schedule_candidate ALTER COLUMN total_shifted TYPE DOUBLE PRECISION;
@@ -0,0 +1,3 @@
+ALTER TABLE schedule DROP COLUMN term_id;
+
+DROP TABLE term;
@@ -0,0 +1,12 @@
+CREATE TABLE term
+(
+    id         UUID             PRIMARY KEY NOT NULL,
+    name       TEXT             NOT NULL,
+    starts_on  DATE             NOT NULL,
+    ends_on    DATE             NOT NULL,
+    carry_over DOUBLE PRECISION NOT NULL DEFAULT 0,
+
+    CHECK (starts_on <= ends_on)
+);
+
+ALTER TABLE schedule ADD term_id UUID REFERENCES term (id) ON DELETE SET NULL DEFAULT NULL;
@@ -0,0 +1,8 @@
+CREATE TABLE subject_target
+(
+    subject_id UUID REFERENCES subject (id) PRIMARY KEY NOT NULL,
+    weekly_min INT DEFAULT NULL CHECK (weekly_min >= 0),
+    weekly_max INT DEFAULT NULL CHECK (weekly_max >= 0),
+    term_min   INT DEFAULT NULL CHECK (term_min >= 0),
+    term_max   INT DEFAULT NULL CHECK (term_max >= 0)
+);
@@ -0,0 +1,4 @@
+ALTER TABLE subject
+    DROP COLUMN left_at,
+    DROP COLUMN joined_at,
+    DROP COLUMN active;
@@ -0,0 +1,11 @@
+ALTER TABLE subject
+    ADD COLUMN active    BOOLEAN     NOT NULL DEFAULT TRUE,
+    ADD COLUMN joined_at TIMESTAMPTZ DEFAULT NULL,
+    ADD COLUMN left_at   TIMESTAMPTZ DEFAULT NULL;
+
+-- Existing subjects joined at some unknown time, so only new ones are stamped.
+ALTER TABLE subject
+    ALTER COLUMN joined_at SET DEFAULT now();
+
+-- Subjects used to be retired by suffixing their name with an asterisk.
+UPDATE subject SET active = FALSE WHERE name LIKE '%*';
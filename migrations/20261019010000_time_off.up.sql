@@ -0,0 +1,10 @@
+CREATE TABLE time_off
+(
+    id         UUID PRIMARY KEY NOT NULL,
+    subject_id UUID REFERENCES subject (id) ON DELETE CASCADE NOT NULL,
+    starts_on  DATE NOT NULL,
+    ends_on    DATE NOT NULL,
+    reason     TEXT DEFAULT NULL,
+
+    CHECK (starts_on <= ends_on)
+);
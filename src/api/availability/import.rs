@@ -33,6 +33,7 @@ pub struct ImportResponse {
     pub slots_imported: i32,
 }
 
+#[allow(clippy::regex_creation_in_loops)]
 pub async fn import(
     State(app): State<Application>,
     Json(body): Json<ImportRequest>,
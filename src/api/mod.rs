@@ -12,10 +12,14 @@ use crate::Config;
 
 mod availability;
 mod health;
+mod pairing;
 mod parameters;
+mod role;
 mod schedule;
 mod slot;
 mod subject;
+mod term;
+mod time_off;
 
 pub type ApiResult<T> = Result<Json<T>, ApiError>;
 
@@ -71,9 +75,13 @@ pub fn create_router(app: ApplicationData) -> Router {
     Router::new()
         .merge(availability::create_router())
         .merge(health::create_router())
+        .merge(pairing::create_router())
         .merge(parameters::create_router())
+        .merge(role::create_router())
         .merge(schedule::create_router())
         .merge(slot::create_router())
         .merge(subject::create_router())
+        .merge(term::create_router())
+        .merge(time_off::create_router())
         .with_state(Application::new(app))
 }
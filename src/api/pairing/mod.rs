@@ -0,0 +1,164 @@
+use axum::{
+    extract::{Path, Query, State},
+    routing::get,
+    Json, Router,
+};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use souvenir::Id;
+
+use crate::{ApiError, ApiResult, Application, PairingKind, PairingRule, Subject};
+
+#[derive(Clone, Serialize)]
+struct ApiPairingRule {
+    id: Id,
+    kind: PairingKind,
+    subject: Id,
+    partner: Id,
+}
+
+impl From<PairingRule> for ApiPairingRule {
+    fn from(rule: PairingRule) -> Self {
+        Self {
+            id: rule.id,
+            kind: rule.kind,
+            subject: rule.subject,
+            partner: rule.partner,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct RulesQuery {
+    /// Only list rules involving this subject.
+    subject: Option<Id>,
+}
+
+async fn rules(
+    State(state): State<Application>,
+    Query(query): Query<RulesQuery>,
+) -> ApiResult<Vec<ApiPairingRule>> {
+    let mut conn = state.pool.acquire().await?;
+    let result = PairingRule::all_rules(&mut conn).await?;
+
+    Ok(Json(
+        result
+            .into_iter()
+            .filter(|rule| {
+                query
+                    .subject
+                    .is_none_or(|subject| rule.subject == subject || rule.partner == subject)
+            })
+            .map(ApiPairingRule::from)
+            .collect(),
+    ))
+}
+
+async fn rule(
+    State(state): State<Application>,
+    Path(id): Path<String>,
+) -> ApiResult<ApiPairingRule> {
+    let id = Id::parse(&id)?;
+
+    let mut conn = state.pool.acquire().await?;
+    Ok(Json(PairingRule::find(id, &mut conn).await?.into()))
+}
+
+#[derive(Clone, Deserialize)]
+struct RuleBody {
+    kind: PairingKind,
+    subject: Id,
+    partner: Id,
+}
+
+impl RuleBody {
+    /// Checks the rule, which replaces the rule `id` when updating one.
+    async fn validate(
+        &self,
+        id: Option<Id>,
+        conn: &mut sqlx::PgConnection,
+    ) -> Result<(), ApiError> {
+        let duplicate = PairingRule::all_rules(&mut *conn)
+            .await?
+            .into_iter()
+            .any(|rule| {
+                Some(rule.id) != id
+                    && rule.kind == self.kind
+                    && rule.subject == self.subject
+                    && rule.partner == self.partner
+            });
+
+        let error = if self.subject == self.partner {
+            Some(anyhow::anyhow!(
+                "A subject cannot be paired with themselves"
+            ))
+        } else if Subject::find(self.subject, &mut *conn).await.is_err() {
+            Some(anyhow::anyhow!("Unknown subject {}", self.subject))
+        } else if Subject::find(self.partner, &mut *conn).await.is_err() {
+            Some(anyhow::anyhow!("Unknown subject {}", self.partner))
+        } else if duplicate {
+            Some(anyhow::anyhow!("The same rule already exists"))
+        } else {
+            None
+        };
+
+        match error {
+            Some(error) => Err(ApiError {
+                status_code: StatusCode::BAD_REQUEST,
+                error,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+async fn create(
+    State(state): State<Application>,
+    Json(body): Json<RuleBody>,
+) -> ApiResult<ApiPairingRule> {
+    let mut conn = state.pool.acquire().await?;
+    body.validate(None, &mut conn).await?;
+
+    let rule = PairingRule::new(body.kind, body.subject, body.partner);
+    rule.upsert(&mut conn).await?;
+
+    Ok(Json(rule.into()))
+}
+
+async fn update(
+    State(state): State<Application>,
+    Path(id): Path<String>,
+    Json(body): Json<RuleBody>,
+) -> ApiResult<ApiPairingRule> {
+    let id = Id::parse(&id)?;
+
+    let mut conn = state.pool.acquire().await?;
+    body.validate(Some(id), &mut conn).await?;
+
+    let mut rule = PairingRule::find(id, &mut conn).await?;
+    rule.kind = body.kind;
+    rule.subject = body.subject;
+    rule.partner = body.partner;
+    rule.upsert(&mut conn).await?;
+
+    Ok(Json(rule.into()))
+}
+
+async fn delete(State(state): State<Application>, Path(id): Path<String>) -> ApiResult<Value> {
+    let id = Id::parse(&id)?;
+
+    let mut conn = state.pool.acquire().await?;
+    PairingRule::find(id, &mut conn)
+        .await?
+        .delete(&mut conn)
+        .await?;
+
+    Ok(Json(json!({ "status": "ok" })))
+}
+
+pub fn create_router() -> Router<Application> {
+    Router::new()
+        .route("/pairing-rules", get(rules).post(create))
+        .route("/pairing-rule/{id}", get(rule).post(update).delete(delete))
+}
@@ -0,0 +1,81 @@
+use axum::{
+    extract::{Path, State},
+    routing::get,
+    Json, Router,
+};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use souvenir::Id;
+
+use crate::{ApiError, ApiResult, Application, Role};
+
+#[derive(Clone, Serialize)]
+pub struct ApiRole {
+    pub id: Id,
+    pub name: String,
+}
+
+impl From<Role> for ApiRole {
+    fn from(role: Role) -> Self {
+        Self {
+            id: role.id,
+            name: role.name,
+        }
+    }
+}
+
+async fn roles(State(state): State<Application>) -> ApiResult<Vec<ApiRole>> {
+    let mut conn = state.pool.acquire().await?;
+    let result = Role::all_roles(&mut conn).await?;
+
+    Ok(Json(result.into_iter().map(ApiRole::from).collect()))
+}
+
+async fn role(State(state): State<Application>, Path(id): Path<String>) -> ApiResult<ApiRole> {
+    let id = Id::parse(&id)?;
+
+    let mut conn = state.pool.acquire().await?;
+    Ok(Json(Role::find(id, &mut conn).await?.into()))
+}
+
+#[derive(Clone, Deserialize)]
+struct RoleBody {
+    name: String,
+}
+
+async fn create(
+    State(state): State<Application>,
+    Json(body): Json<RoleBody>,
+) -> ApiResult<ApiRole> {
+    let name = body.name.trim();
+
+    let mut conn = state.pool.acquire().await?;
+
+    if name.is_empty() || Role::find_by_name(name, &mut conn).await?.is_some() {
+        return Err(ApiError {
+            status_code: StatusCode::BAD_REQUEST,
+            error: anyhow::anyhow!("Role names must be non-empty and unique"),
+        });
+    }
+
+    let role = Role::new(name.to_string());
+    role.upsert(&mut conn).await?;
+
+    Ok(Json(role.into()))
+}
+
+async fn delete(State(state): State<Application>, Path(id): Path<String>) -> ApiResult<Value> {
+    let id = Id::parse(&id)?;
+
+    let mut conn = state.pool.acquire().await?;
+    Role::find(id, &mut conn).await?.delete(&mut conn).await?;
+
+    Ok(Json(json!({ "status": "ok" })))
+}
+
+pub fn create_router() -> Router<Application> {
+    Router::new()
+        .route("/roles", get(roles).post(create))
+        .route("/role/{id}", get(role).delete(delete))
+}
@@ -0,0 +1,319 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use souvenir::Id;
+use sqlx::PgConnection;
+
+use super::generate::local_day;
+use crate::{
+    api::ApiResult, ApiError, Application, Availability, Config, Schedule, Slot, Subject, TimeOff,
+};
+
+/// A subject on a slot.
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Seat {
+    pub slot: Id,
+    pub subject: Id,
+}
+
+#[derive(Deserialize)]
+pub struct AssignBody {
+    #[serde(flatten)]
+    seat: Seat,
+    /// Assign the subject even if they are inactive, on time off or not
+    /// available for the slot.
+    #[serde(default, rename = "override")]
+    force: bool,
+}
+
+#[derive(Deserialize)]
+pub struct UnassignBody {
+    #[serde(flatten)]
+    seat: Seat,
+}
+
+#[derive(Deserialize)]
+pub struct SwapBody {
+    first: Seat,
+    second: Seat,
+    /// Swap the subjects even if either is inactive, on time off or not
+    /// available for their new slot.
+    #[serde(default, rename = "override")]
+    force: bool,
+}
+
+#[derive(Serialize)]
+pub struct EditResponse {
+    /// Schedule the edit was applied to.
+    pub id: Id,
+    /// Set when the edited schedule was published, and a revision of it was
+    /// created to hold the edit instead.
+    pub revised_from: Option<Id>,
+    pub added: Vec<Seat>,
+    pub removed: Vec<Seat>,
+}
+
+fn rejected(error: anyhow::Error) -> ApiError {
+    ApiError {
+        status_code: StatusCode::BAD_REQUEST,
+        error,
+    }
+}
+
+/// Rejects a seat whose subject is inactive, on time off, or not available
+/// for its slot in the availability `schedule` was built from, unless
+/// forced.
+async fn check_available(
+    schedule: &Schedule,
+    seat: Seat,
+    force: bool,
+    config: &Config,
+    tx: &mut PgConnection,
+) -> Result<(), ApiError> {
+    let slot = Slot::find(seat.slot, &mut *tx)
+        .await
+        .map_err(|_| rejected(anyhow::anyhow!("Unknown slot {}", seat.slot)))?;
+    let subject = Subject::find(seat.subject, &mut *tx)
+        .await
+        .map_err(|_| rejected(anyhow::anyhow!("Unknown subject {}", seat.subject)))?;
+
+    if force {
+        return Ok(());
+    }
+
+    if !subject.active {
+        return Err(rejected(anyhow::anyhow!(
+            "{} is inactive; pass `override` to assign them anyway",
+            seat.subject
+        )));
+    }
+
+    if let Some(day) = local_day(slot.w2m_id, config) {
+        if let Some(time_off) = TimeOff::covering(seat.subject, day, &mut *tx).await? {
+            return Err(rejected(anyhow::anyhow!(
+                "{} falls in time off {} of {}; pass `override` to assign them anyway",
+                seat.slot,
+                time_off.id,
+                seat.subject
+            )));
+        }
+    }
+
+    let availability = match schedule.availability {
+        Some(availability) => Availability::find(availability, &mut *tx).await?,
+        None => Availability::fetch_current(&mut *tx).await?,
+    };
+
+    if !availability
+        .for_slot(seat.slot, &mut *tx)
+        .await?
+        .contains(&seat.subject)
+    {
+        return Err(rejected(anyhow::anyhow!(
+            "{} is not available for {}; pass `override` to assign them anyway",
+            seat.subject,
+            seat.slot
+        )));
+    }
+
+    Ok(())
+}
+
+/// The schedule an edit should be applied to. A published schedule is left
+/// as it is, and a revision carrying over its assignments is created, made
+/// current in its place if it was, and returned instead.
+async fn editable(
+    schedule: Schedule,
+    tx: &mut PgConnection,
+) -> anyhow::Result<(Schedule, Option<Id>)> {
+    if schedule.published_at.is_none() {
+        return Ok((schedule, None));
+    }
+
+    let mut revision = schedule.revise(None);
+    revision.upsert(&mut *tx).await?;
+
+    for (slot, subject) in schedule.assignments(&mut *tx).await? {
+        revision.add(slot, subject, &mut *tx).await?;
+    }
+
+    sqlx::query!(
+        "UPDATE parameters SET schedule = $1 WHERE schedule = $2;",
+        revision.id as Id,
+        schedule.id as Id,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    Ok((revision, Some(schedule.id)))
+}
+
+pub async fn assign(
+    State(state): State<Application>,
+    Path(id): Path<String>,
+    Json(body): Json<AssignBody>,
+) -> ApiResult<EditResponse> {
+    let id = Id::parse(&id)?;
+    let seat = body.seat;
+
+    let mut tx = state.pool.begin().await?;
+    let schedule = Schedule::resolve(id, &mut tx).await?;
+
+    if schedule
+        .assignments(&mut tx)
+        .await?
+        .contains(&(seat.slot, seat.subject))
+    {
+        return Err(rejected(anyhow::anyhow!(
+            "{} is already assigned to {}",
+            seat.subject,
+            seat.slot
+        )));
+    }
+
+    check_available(&schedule, seat, body.force, &state.config, &mut tx).await?;
+
+    let (schedule, revised_from) = editable(schedule, &mut tx).await?;
+    schedule.add(seat.slot, seat.subject, &mut tx).await?;
+    tx.commit().await?;
+
+    Ok(Json(EditResponse {
+        id: schedule.id,
+        revised_from,
+        added: vec![seat],
+        removed: Vec::new(),
+    }))
+}
+
+pub async fn unassign(
+    State(state): State<Application>,
+    Path(id): Path<String>,
+    Json(body): Json<UnassignBody>,
+) -> ApiResult<EditResponse> {
+    let id = Id::parse(&id)?;
+    let seat = body.seat;
+
+    let mut tx = state.pool.begin().await?;
+    let schedule = Schedule::resolve(id, &mut tx).await?;
+
+    if !schedule
+        .assignments(&mut tx)
+        .await?
+        .contains(&(seat.slot, seat.subject))
+    {
+        return Err(rejected(anyhow::anyhow!(
+            "{} is not assigned to {}",
+            seat.subject,
+            seat.slot
+        )));
+    }
+
+    let (schedule, revised_from) = editable(schedule, &mut tx).await?;
+    schedule.remove(seat.slot, seat.subject, &mut tx).await?;
+    tx.commit().await?;
+
+    Ok(Json(EditResponse {
+        id: schedule.id,
+        revised_from,
+        added: Vec::new(),
+        removed: vec![seat],
+    }))
+}
+
+/// Moves `first.subject` to `second.slot` and `second.subject` to
+/// `first.slot`.
+pub async fn swap(
+    State(state): State<Application>,
+    Path(id): Path<String>,
+    Json(body): Json<SwapBody>,
+) -> ApiResult<EditResponse> {
+    let id = Id::parse(&id)?;
+    let (first, second) = (body.first, body.second);
+
+    let added = vec![
+        Seat {
+            slot: second.slot,
+            subject: first.subject,
+        },
+        Seat {
+            slot: first.slot,
+            subject: second.subject,
+        },
+    ];
+
+    let mut tx = state.pool.begin().await?;
+    let schedule = Schedule::resolve(id, &mut tx).await?;
+    let assignments = schedule.assignments(&mut tx).await?;
+
+    if first.slot == second.slot || first.subject == second.subject {
+        return Err(rejected(anyhow::anyhow!(
+            "A swap needs two different slots and subjects"
+        )));
+    }
+
+    for seat in [first, second] {
+        if !assignments.contains(&(seat.slot, seat.subject)) {
+            return Err(rejected(anyhow::anyhow!(
+                "{} is not assigned to {}",
+                seat.subject,
+                seat.slot
+            )));
+        }
+    }
+
+    for &seat in added.iter() {
+        if assignments.contains(&(seat.slot, seat.subject)) {
+            return Err(rejected(anyhow::anyhow!(
+                "{} is already assigned to {}",
+                seat.subject,
+                seat.slot
+            )));
+        }
+
+        check_available(&schedule, seat, body.force, &state.config, &mut tx).await?;
+    }
+
+    let (schedule, revised_from) = editable(schedule, &mut tx).await?;
+
+    for seat in [first, second] {
+        schedule.remove(seat.slot, seat.subject, &mut tx).await?;
+    }
+
+    for &seat in added.iter() {
+        schedule.add(seat.slot, seat.subject, &mut tx).await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(Json(EditResponse {
+        id: schedule.id,
+        revised_from,
+        added,
+        removed: vec![first, second],
+    }))
+}
+
+#[derive(Serialize)]
+pub struct PublishResponse {
+    pub id: Id,
+    pub published_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+pub async fn publish(
+    State(state): State<Application>,
+    Path(id): Path<String>,
+) -> ApiResult<PublishResponse> {
+    let id = Id::parse(&id)?;
+
+    let mut conn = state.pool.acquire().await?;
+    let mut schedule = Schedule::resolve(id, &mut conn).await?;
+    schedule.publish(&mut conn).await?;
+
+    Ok(Json(PublishResponse {
+        id: schedule.id,
+        published_at: schedule.published_at,
+    }))
+}
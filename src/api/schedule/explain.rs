@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use souvenir::Id;
+
+use crate::{api::ApiResult, Application};
+
+#[derive(Deserialize)]
+pub struct ExplainQuery {
+    /// Only explain slots this subject was considered for.
+    subject: Option<Id>,
+}
+
+#[derive(Serialize)]
+pub struct ApiResponse {
+    pub id: Id,
+    pub slots: Vec<ApiSlot>,
+}
+
+#[derive(Serialize)]
+pub struct ApiSlot {
+    pub slot: Id,
+    pub w2m_id: Option<i32>,
+    pub chosen: Vec<Id>,
+    pub candidates: Vec<ApiCandidate>,
+}
+
+#[derive(Serialize)]
+pub struct ApiCandidate {
+    pub id: Id,
+    pub tag: String,
+    pub name: Option<String>,
+    pub weeks_since: Option<i64>,
+    pub flexibility: i32,
+    pub total_shifted: f64,
+    pub shifts_current: i32,
+    pub weight: f64,
+    pub chosen: bool,
+    pub excluded: Option<String>,
+}
+
+pub async fn explain(
+    State(state): State<Application>,
+    Path(id): Path<String>,
+    Query(query): Query<ExplainQuery>,
+) -> ApiResult<ApiResponse> {
+    let id = Id::parse(&id)?;
+
+    let records = sqlx::query!(
+        r#"
+            SELECT
+                slot_id AS "slot: Id",
+                slot.w2m_id,
+                subject_id AS "subject: Id",
+                subject.tag,
+                subject.name,
+                weeks_since,
+                flexibility,
+                total_shifted,
+                shifts_current,
+                weight,
+                chosen,
+                excluded
+            FROM schedule_candidate
+                INNER JOIN slot ON slot_id = slot.id
+                INNER JOIN subject ON subject_id = subject.id
+                WHERE schedule_id = $1
+                ORDER BY slot.w2m_id ASC, weight DESC;
+        "#,
+        id as Id,
+    )
+    .fetch_all(&state.pool)
+    .await?;
+
+    let mut slots: Vec<ApiSlot> = Vec::new();
+    let mut index: HashMap<Id, usize> = HashMap::new();
+
+    for record in records {
+        let position = *index.entry(record.slot).or_insert_with(|| {
+            slots.push(ApiSlot {
+                slot: record.slot,
+                w2m_id: record.w2m_id,
+                chosen: Vec::new(),
+                candidates: Vec::new(),
+            });
+
+            slots.len() - 1
+        });
+
+        let slot = &mut slots[position];
+
+        if record.chosen {
+            slot.chosen.push(record.subject);
+        }
+
+        slot.candidates.push(ApiCandidate {
+            id: record.subject,
+            tag: record.tag,
+            name: record.name,
+            weeks_since: record.weeks_since,
+            flexibility: record.flexibility,
+            total_shifted: record.total_shifted,
+            shifts_current: record.shifts_current,
+            weight: record.weight,
+            chosen: record.chosen,
+            excluded: record.excluded,
+        });
+    }
+
+    if let Some(subject) = query.subject {
+        slots.retain(|slot| slot.candidates.iter().any(|c| c.id == subject));
+    }
+
+    Ok(Json(ApiResponse { id, slots }))
+}
@@ -1,10 +1,11 @@
-use crate::{ApiError, Application};
+use crate::{ApiError, Application, Availability, Schedule};
 use axum::extract::{Path, Query, State};
 use axum::http::{header, HeaderMap};
 use axum::response::IntoResponse;
 use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
 use souvenir::Id;
+use std::collections::HashMap;
 use std::fmt::Write;
 
 #[derive(Deserialize)]
@@ -15,7 +16,8 @@ pub struct ExportQuery {
 #[derive(Serialize)]
 pub struct SlotAssignment {
     pub slot_w2m_id: i32,
-    pub subject_name: String,
+    pub seats: usize,
+    pub subject_names: Vec<String>,
 }
 
 pub async fn export(
@@ -23,24 +25,49 @@ pub async fn export(
     Path(id): Path<String>,
     Query(query): Query<ExportQuery>,
 ) -> Result<impl IntoResponse, crate::api::ApiError> {
+    let mut conn = state.pool.acquire().await?;
+    let schedule = Schedule::resolve(Id::parse(&id)?, &mut conn).await?;
+
     let records = sqlx::query!(
         r#"
-            SELECT slot.w2m_id, subject.name, subject.tag FROM schedule_assignment
-                INNER JOIN slot ON schedule_assignment.slot_id = slot.id
+            SELECT
+                schedule_assignment.slot_id AS "slot: Id",
+                subject.name,
+                subject.tag
+            FROM schedule_assignment
                 INNER JOIN subject ON schedule_assignment.subject_id = subject.id
                 WHERE schedule_assignment.schedule_id = $1
-                ORDER BY slot.w2m_id ASC, subject.name ASC;
+                ORDER BY subject.name ASC;
         "#,
-        Id::parse(&id)? as Id,
+        schedule.id as Id,
     )
-    .fetch_all(&state.pool)
+    .fetch_all(&mut *conn)
     .await?;
 
-    let assignments: Vec<SlotAssignment> = records
+    let mut names: HashMap<Id, Vec<String>> = HashMap::new();
+
+    for row in records {
+        names
+            .entry(row.slot)
+            .or_default()
+            .push(row.name.unwrap_or(row.tag));
+    }
+
+    let availability = match schedule.availability {
+        Some(availability) => Availability::find(availability, &mut conn).await?,
+        None => Availability::fetch_current(&mut conn).await?,
+    };
+
+    // Every slot of the schedule's week gets a cell, filled or not, so the
+    // grid stays aligned.
+    let assignments: Vec<SlotAssignment> = availability
+        .slots(&mut conn)
+        .await?
         .into_iter()
-        .map(|row| SlotAssignment {
-            slot_w2m_id: row.w2m_id.unwrap_or_default(),
-            subject_name: row.name.unwrap_or_else(|| row.tag),
+        .map(|slot| SlotAssignment {
+            slot_w2m_id: slot.w2m_id,
+            seats: slot.staffing(&state.config.scheduler).max as usize,
+            subject_names: names.remove(&slot.id).unwrap_or_default(),
         })
         .collect();
 
@@ -74,31 +101,36 @@ pub async fn export(
 fn build_csv(assignments: &[SlotAssignment], sheets_export: bool) -> String {
     const SLOTS_PER_DAY: usize = 5;
 
-    let mut days: Vec<Vec<(&str, &str)>> = vec![Vec::new(); 5];
-
-    for (i, chunk) in assignments.chunks_exact(2).enumerate() {
-        let day = i / SLOTS_PER_DAY;
-        days[day].push((&chunk[0].subject_name, &chunk[1].subject_name));
-    }
-
+    let days: Vec<&[SlotAssignment]> = assignments.chunks(SLOTS_PER_DAY).collect();
     let mut rows: Vec<Vec<&str>> = Vec::new();
 
     for slot_idx in 0..SLOTS_PER_DAY {
-        let mut row1 = Vec::with_capacity(5);
-        let mut row2 = Vec::with_capacity(5);
-
-        for day in 0..5 {
-            let (s1, s2) = days[day][slot_idx];
-            row1.push(s1);
-            row2.push(s2);
-        }
+        let slots: Vec<Option<&SlotAssignment>> =
+            days.iter().map(|day| day.get(slot_idx)).collect();
+
+        let seats = slots
+            .iter()
+            .flatten()
+            .map(|slot| slot.seats.max(slot.subject_names.len()))
+            .max()
+            .unwrap_or(0);
+
+        let group: Vec<Vec<&str>> = (0..seats)
+            .map(|seat| {
+                slots
+                    .iter()
+                    .map(|slot| {
+                        slot.and_then(|slot| slot.subject_names.get(seat))
+                            .map_or("", |name| name.as_str())
+                    })
+                    .collect()
+            })
+            .collect();
 
-        rows.push(row1);
-        rows.push(row2);
+        rows.extend(group.iter().cloned());
 
         if sheets_export {
-            rows.push(rows[rows.len() - 2].clone());
-            rows.push(rows[rows.len() - 2].clone());
+            rows.extend(group);
         }
     }
 
@@ -1,30 +1,140 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use axum::{extract::State, Json};
+use chrono::{DateTime, NaiveDate};
+use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
-use souvenir::{Id, Identifiable};
+use serde_json::Value;
+use souvenir::Id;
+use sqlx::PgConnection;
 
-use crate::{ApiResult, Application, Availability, Schedule};
+use super::pins::{self, Pin};
+use crate::{
+    ApiError, ApiResult, Application, Availability, Config, Constraints, Deviation, Fairness,
+    Options, Outcome, PairingRule, Problem, Role, Schedule, ShiftTarget, Slot, SlotSpec, Solver,
+    Subject, Targets, Term, TimeOff, Understaffed, Unmet, Unsatisfied,
+};
 
 #[derive(Deserialize)]
 pub struct GenerateRequest {
     pub name: Option<String>,
     pub parent: Option<Id>,
+    /// Scoring strategy and parameters for this run, layered over the
+    /// configured `[scheduler.scoring]` settings.
+    #[serde(default)]
+    pub scoring: Value,
+    #[serde(default)]
+    pub solver: Solver,
+    /// Run a local-search pass over the solver's result before saving.
+    #[serde(default)]
+    pub improve: bool,
+    /// Return the proposed schedule without saving it or making it current.
+    #[serde(default)]
+    pub preview: bool,
+    /// Assignments to place before generating, on top of any pinned on the
+    /// parent schedule.
+    #[serde(default)]
+    pub pins: Vec<Pin>,
+    /// Perturbs candidate scores slightly, by up to the configured `jitter`,
+    /// and decides how exact ties are broken. Generating again with the same seed, settings and data gives the same
+    /// schedule.
+    #[serde(default)]
+    pub seed: u64,
+    /// Term to file the schedule under. Defaults to the term the first slot
+    /// of the availability falls in.
+    pub term: Option<Id>,
+    /// Weight of shifts from schedules outside the term, from 0 to count
+    /// only shifts within the term to 1 to count every shift in full.
+    /// Defaults to the term's own setting.
+    pub carry_over: Option<f64>,
+    /// Number of alternatives to generate, with consecutive seeds starting
+    /// at `seed`. The fairest is returned, and saved unless previewing; the
+    /// rest are returned as previews under `alternatives`, each distinct
+    /// schedule once.
+    pub candidates: Option<u32>,
 }
 
+/// Most alternatives a single request may generate.
+const MAX_CANDIDATES: u32 = 16;
+
 #[derive(Serialize)]
 pub struct GenerateResponse {
     pub name: Option<String>,
+    /// Id of the new schedule. Previews are never saved under it.
     pub id: Id,
     pub parent: Option<Id>,
+    pub preview: bool,
+    pub understaffed: Vec<Understaffed>,
+    /// Role requirements the generated schedule falls short of.
+    pub unmet: Vec<Unmet>,
+    /// Pairing rules the generated schedule breaks.
+    pub unsatisfied: Vec<Unsatisfied>,
+    /// How far every subject with a shift target lands from it.
+    pub deviations: Vec<Deviation>,
+    pub improvements: u32,
+    pub slots: Vec<PlannedSlot>,
+    pub fairness: Fairness,
+    /// Summed weight of every assignment.
+    pub objective: f64,
+    pub seed: u64,
+    /// Availability left out because the subject has time off.
+    pub withdrawn: Vec<Withdrawn>,
+    /// Other candidates generated alongside this one, fairest first.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub alternatives: Vec<GenerateResponse>,
+}
+
+/// An availability entry overridden by time off.
+#[derive(Clone, Serialize)]
+pub struct Withdrawn {
+    pub slot: Id,
+    pub subject: Id,
+    pub time_off: Id,
+    pub reason: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct PlannedSlot {
+    pub slot: Id,
+    pub w2m_id: i32,
+    pub assignments: Vec<PlannedAssignment>,
+    pub weight: f64,
+}
+
+#[derive(Serialize)]
+pub struct PlannedAssignment {
+    pub subject: Id,
+    pub weight: f64,
+    pub pinned: bool,
 }
 
 pub async fn generate(
     State(state): State<Application>,
     Json(body): Json<GenerateRequest>,
 ) -> ApiResult<GenerateResponse> {
+    let scoring = state
+        .config
+        .scheduler
+        .scoring
+        .merge(body.scoring)
+        .map_err(|error| ApiError {
+            status_code: StatusCode::BAD_REQUEST,
+            error,
+        })?;
+
+    state
+        .config
+        .scheduler
+        .decay
+        .validate()
+        .map_err(|error| ApiError {
+            status_code: StatusCode::BAD_REQUEST,
+            error,
+        })?;
+
     let mut tx = state.pool.begin().await?;
 
+    let pins = pins::validate(body.pins, &mut tx).await?;
     let availability = Availability::fetch_current(&mut tx).await?;
 
     let parent_id = match body.parent {
@@ -32,54 +142,443 @@ pub async fn generate(
         None => Schedule::fetch_current(&mut tx).await.ok().map(|s| s.id),
     };
 
+    let candidates = body.candidates.unwrap_or(1);
+
+    if candidates == 0 || candidates > MAX_CANDIDATES {
+        return Err(ApiError {
+            status_code: StatusCode::BAD_REQUEST,
+            error: anyhow::anyhow!("candidates must be between 1 and {MAX_CANDIDATES}"),
+        });
+    }
+
+    if body.carry_over.is_some_and(|c| !(0.0..=1.0).contains(&c)) {
+        return Err(ApiError {
+            status_code: StatusCode::BAD_REQUEST,
+            error: anyhow::anyhow!("carry_over must be between 0 and 1"),
+        });
+    }
+
+    let term = match body.term {
+        Some(id) => Some(Term::find(id, &mut tx).await.map_err(|_| ApiError {
+            status_code: StatusCode::BAD_REQUEST,
+            error: anyhow::anyhow!("Unknown term {id}"),
+        })?),
+        None => term_of(&availability, &state.config, &mut tx).await?,
+    };
+    let pinned: Vec<Id> = pins.iter().map(|pin| pin.slot).collect();
+    let carry_over = term
+        .as_ref()
+        .map(|term| body.carry_over.unwrap_or(term.carry_over));
+
     let mut schedule = Schedule::new(parent_id, body.name);
-    schedule.upsert(&mut tx).await?;
+    schedule.term = term.map(|term| term.id);
 
-    for (slot, mut subjects) in availability.sorted_by_flexibility(&mut tx).await? {
-        let mut weights: HashMap<Id, f64> = HashMap::new();
+    let (mut problem, withdrawn) = load_problem(
+        &schedule,
+        &availability,
+        &pinned,
+        carry_over,
+        &state.config,
+        &mut tx,
+    )
+    .await?;
 
-        for &subject in subjects.iter() {
-            weights.insert(
-                subject,
-                weight(&schedule, &availability, subject, &mut tx).await?,
-            );
+    for pin in pins {
+        if let Some(time_off) = problem.time_off.get(&(pin.slot, pin.subject)) {
+            return Err(ApiError {
+                status_code: StatusCode::BAD_REQUEST,
+                error: anyhow::anyhow!(
+                    "Pin ({}, {}) falls in time off {time_off}",
+                    pin.slot,
+                    pin.subject,
+                ),
+            });
         }
 
-        subjects.sort_by(|a, b| weights[b].total_cmp(&weights[a]));
+        if !problem.pins.contains(&(pin.slot, pin.subject)) {
+            problem.pins.push((pin.slot, pin.subject));
+        }
+    }
+
+    let mut generated: Vec<(Options, Outcome)> = (0..candidates as u64)
+        .map(|i| {
+            let options = Options {
+                scoring: scoring.clone(),
+                solver: body.solver,
+                improve: body.improve,
+                seed: body.seed.wrapping_add(i),
+            };
+            let outcome = problem.solve(&options);
+
+            (options, outcome)
+        })
+        .collect();
+
+    // Seeds that settle on the same schedule are only offered once.
+    let mut seen: HashSet<Vec<(Id, Id)>> = HashSet::new();
+
+    generated.retain(|(_, outcome)| {
+        let mut assignments = outcome.assignments.clone();
+        assignments.sort();
+        seen.insert(assignments)
+    });
+
+    // Fewest understaffed slots first, then the most even spread of shifts.
+    generated.sort_by(|(_, a), (_, b)| {
+        let stddev = |outcome: &Outcome| outcome.fairness.stddev.unwrap_or(0.0);
+
+        a.understaffed
+            .len()
+            .cmp(&b.understaffed.len())
+            .then(stddev(a).total_cmp(&stddev(b)))
+    });
+
+    let mut generated = generated.into_iter();
+    let (options, outcome) = generated.next().expect("at least one candidate");
+    let mut settings = settings(&state.config)?;
 
-        schedule.add(slot, subjects[0], &mut tx).await?;
-        schedule.add(slot, subjects[1], &mut tx).await?;
+    if let Some(carry_over) = carry_over {
+        settings["carry_over"] = carry_over.into();
     }
 
-    sqlx::query!("UPDATE parameters SET schedule = $1;", schedule.id as Id)
-        .execute(&mut *tx)
-        .await?;
+    // Pins from the request are not stored anywhere else, so every pin the
+    // schedule was generated with is recorded to reproduce it.
+    let placed: Vec<Pin> = problem
+        .pins
+        .iter()
+        .map(|&(slot, subject)| Pin { slot, subject })
+        .collect();
+    settings["pins"] = serde_json::to_value(placed)?;
+
+    schedule.generated(&availability, &options, settings.clone())?;
+
+    if !body.preview {
+        schedule.upsert(&mut tx).await?;
+        save(&schedule, &outcome, &mut tx).await?;
+
+        sqlx::query!("UPDATE parameters SET schedule = $1;", schedule.id as Id)
+            .execute(&mut *tx)
+            .await?;
+    }
 
     tx.commit().await?;
 
-    Ok(Json(GenerateResponse {
+    let mut alternatives = Vec::new();
+
+    for (options, outcome) in generated {
+        let mut alternative = Schedule::new(parent_id, schedule.name.clone());
+        alternative.generated(&availability, &options, settings.clone())?;
+        let mut response = respond(alternative, true, options.seed, &problem, outcome);
+        response.withdrawn = withdrawn.clone();
+        alternatives.push(response);
+    }
+
+    let mut response = respond(schedule, body.preview, options.seed, &problem, outcome);
+    response.withdrawn = withdrawn;
+    response.alternatives = alternatives;
+
+    Ok(Json(response))
+}
+
+/// Scheduler settings besides scoring that shape a generated schedule, as
+/// recorded alongside it.
+pub fn settings(config: &Config) -> anyhow::Result<Value> {
+    let mut settings = serde_json::to_value(&config.scheduler)?;
+
+    if let Value::Object(settings) = &mut settings {
+        settings.remove("scoring");
+    }
+
+    Ok(settings)
+}
+
+/// Saves the assignments of `outcome` and how their candidates were weighed.
+pub async fn save(
+    schedule: &Schedule,
+    outcome: &Outcome,
+    tx: &mut PgConnection,
+) -> anyhow::Result<()> {
+    for &(slot, subject) in outcome.assignments.iter() {
+        schedule.add(slot, subject, &mut *tx).await?;
+    }
+
+    for consideration in outcome.considerations.iter() {
+        schedule.record(consideration, &mut *tx).await?;
+    }
+
+    Ok(())
+}
+
+pub fn respond(
+    schedule: Schedule,
+    preview: bool,
+    seed: u64,
+    problem: &Problem,
+    outcome: Outcome,
+) -> GenerateResponse {
+    let mut planned: HashMap<Id, Vec<PlannedAssignment>> = HashMap::new();
+
+    for (i, (&(slot, subject), &weight)) in
+        outcome.assignments.iter().zip(&outcome.weights).enumerate()
+    {
+        planned.entry(slot).or_default().push(PlannedAssignment {
+            subject,
+            weight,
+            pinned: i < problem.pins.len(),
+        });
+    }
+
+    let slots = problem
+        .slots
+        .iter()
+        .filter_map(|slot| {
+            let assignments = planned.remove(&slot.id)?;
+
+            Some(PlannedSlot {
+                slot: slot.id,
+                w2m_id: slot.w2m_id,
+                weight: assignments.iter().map(|a| a.weight).sum(),
+                assignments,
+            })
+        })
+        .collect();
+
+    GenerateResponse {
         id: schedule.id,
         parent: schedule.parent,
         name: schedule.name,
-    }))
+        preview,
+        understaffed: outcome.understaffed,
+        unmet: outcome.unmet,
+        unsatisfied: outcome.unsatisfied,
+        deviations: outcome.deviations,
+        improvements: outcome.improvements,
+        slots,
+        fairness: outcome.fairness,
+        objective: outcome.objective,
+        seed,
+        withdrawn: Vec::new(),
+        alternatives: Vec::new(),
+    }
+}
+
+/// Calendar day a slot starting at `w2m_id` falls on, in the configured
+/// time zone.
+pub fn local_day(w2m_id: i32, config: &Config) -> Option<NaiveDate> {
+    let offset = config.scheduler.utc_offset_hours as i64 * 60 * 60;
+
+    DateTime::from_timestamp(w2m_id as i64 + offset, 0).map(|start| start.date_naive())
+}
+
+/// Calendar day the first slot of `availability` starts on, if any.
+async fn first_day(
+    availability: &Availability,
+    config: &Config,
+    tx: &mut PgConnection,
+) -> anyhow::Result<Option<NaiveDate>> {
+    let entries = availability.entries(&mut *tx).await?;
+    let first = Slot::all_slots(&mut *tx)
+        .await?
+        .into_iter()
+        .filter(|slot| entries.contains_key(&slot.id))
+        .map(|slot| slot.w2m_id)
+        .min();
+
+    Ok(first.and_then(|w2m_id| local_day(w2m_id, config)))
+}
+
+/// The term the first slot of `availability` starts in, if any.
+async fn term_of(
+    availability: &Availability,
+    config: &Config,
+    tx: &mut PgConnection,
+) -> anyhow::Result<Option<Term>> {
+    match first_day(availability, config, &mut *tx).await? {
+        Some(day) => Term::containing(day, tx).await,
+        None => Ok(None),
+    }
 }
 
-async fn weight(
+/// Shift targets for the week of `schedule`. Term totals are spread evenly
+/// over the weeks left in `term` from `day`, after the shifts already held
+/// in the term's other schedules, and tighten the weekly targets.
+async fn shift_targets(
+    schedule: &Schedule,
+    term: Option<&Term>,
+    day: Option<NaiveDate>,
+    tx: &mut PgConnection,
+) -> anyhow::Result<HashMap<Id, ShiftTarget>> {
+    let targets = Targets::all(&mut *tx).await?;
+    let mut held: HashMap<Id, u32> = HashMap::new();
+
+    if let Some(term) = term {
+        for (other, _, subject) in term.assignments(schedule, &mut *tx).await? {
+            if other != schedule.id {
+                *held.entry(subject).or_default() += 1;
+            }
+        }
+    }
+
+    let weeks_left = match (term, day) {
+        (Some(term), Some(day)) => (term.ends_on - day).num_days().max(0) / 7 + 1,
+        _ => 1,
+    };
+
+    Ok(targets
+        .into_iter()
+        .map(|(subject, targets)| {
+            let target = ShiftTarget {
+                min: targets.weekly_min.map(|min| min as u32),
+                max: targets.weekly_max.map(|max| max as u32),
+            };
+
+            let target = match term {
+                Some(_) => target.paced(
+                    targets.term_min.map(|min| min as u32),
+                    targets.term_max.map(|max| max as u32),
+                    held.get(&subject).copied().unwrap_or(0),
+                    weeks_left as u32,
+                ),
+                None => target,
+            };
+
+            (subject, target)
+        })
+        .collect())
+}
+
+/// Gathers everything the engine needs to generate `schedule` from
+/// `availability`, including the pins persisted on the schedule it follows.
+/// Only the slots of `availability` are scheduled, along with the `pinned`
+/// slots and those of persisted pins. Inactive subjects and time off are handed to the engine, which keeps
+/// them out of the availability; pins falling foul of either are dropped
+/// here, and the availability withdrawn for time off is returned alongside.
+/// When the schedule belongs to a term, shifts from outside it are weighed
+/// by `carry_over`, or the term's own setting if not given.
+pub async fn load_problem(
     schedule: &Schedule,
     availability: &Availability,
-    subject: impl Identifiable,
-    tx: &mut sqlx::PgConnection,
-) -> anyhow::Result<f64> {
-    let subject_id = subject.id();
+    pinned: &[Id],
+    carry_over: Option<f64>,
+    config: &Config,
+    tx: &mut PgConnection,
+) -> anyhow::Result<(Problem, Vec<Withdrawn>)> {
+    let mut requirements = Role::requirements(&mut *tx).await?;
+
+    let mut pins = match schedule.predecessor(&mut *tx).await? {
+        Some(predecessor) => predecessor.pins(&mut *tx).await?,
+        None => Vec::new(),
+    };
+
+    // Only this week's slots are scheduled, along with any pinned outside it.
+    let mut slots = availability.slots(&mut *tx).await?;
+
+    for &slot in pinned.iter().chain(pins.iter().map(|(slot, _)| slot)) {
+        if !slots.iter().any(|s| s.id == slot) {
+            slots.push(Slot::find(slot, &mut *tx).await?);
+        }
+    }
+
+    slots.sort_by_key(|slot| slot.w2m_id);
+
+    let slots: Vec<SlotSpec> = slots
+        .into_iter()
+        .map(|slot| SlotSpec {
+            id: slot.id,
+            w2m_id: slot.w2m_id,
+            staffing: slot.staffing(&config.scheduler),
+            requirements: requirements.remove(&slot.id).unwrap_or_default(),
+        })
+        .collect();
+
+    let term = match schedule.term {
+        Some(term) => Some(Term::find(term, &mut *tx).await?),
+        None => None,
+    };
+    let day = first_day(availability, config, &mut *tx).await?;
+
+    let (subjects, left): (Vec<Subject>, Vec<Subject>) = Subject::all_subjects(&mut *tx)
+        .await?
+        .into_iter()
+        .partition(|subject| subject.active);
+    let active: HashSet<Id> = subjects.iter().map(|subject| subject.id).collect();
+    let inactive: HashSet<Id> = left.iter().map(|subject| subject.id).collect();
+
+    let mut targets = shift_targets(schedule, term.as_ref(), day, &mut *tx).await?;
+    targets.retain(|subject, _| active.contains(subject));
+
+    let constraints = Constraints {
+        max_shifts: config.scheduler.max_shifts_per_week,
+        subject_max_shifts: subjects
+            .iter()
+            .filter_map(|subject| Some((subject.id, subject.max_shifts_per_week? as u32)))
+            .collect(),
+        max_shifts_per_day: config.scheduler.max_shifts_per_day,
+        min_rest: config.scheduler.min_rest_hours as i64 * 60 * 60,
+        utc_offset: config.scheduler.utc_offset_hours as i64 * 60 * 60,
+        contiguous_bonus: config.scheduler.contiguous_bonus,
+        pairings: PairingRule::all_rules(&mut *tx)
+            .await?
+            .iter()
+            .map(PairingRule::pairing)
+            .collect(),
+        roles: Role::holders(&mut *tx).await?,
+        targets,
+        target_weight: config.scheduler.target_weight,
+        jitter: config.scheduler.jitter,
+        seed: 0,
+    };
+
+    let time_off = TimeOff::all_time_off(&mut *tx).await?;
+    let mut off: HashMap<(Id, Id), Id> = HashMap::new();
+
+    for slot in slots.iter() {
+        let Some(day) = local_day(slot.w2m_id, config) else {
+            continue;
+        };
+
+        for time_off in time_off.iter().filter(|time_off| time_off.covers(day)) {
+            off.entry((slot.id, time_off.subject))
+                .or_insert(time_off.id);
+        }
+    }
+
+    pins.retain(|pin| active.contains(&pin.1) && !off.contains_key(pin));
 
-    let weeks_since = schedule
-        .last_scheduled(subject_id, tx)
+    let mut history = schedule
+        .history(&mut *tx)
         .await?
-        .unwrap_or(100) as f64
-        - 1.0;
-    let flexibility = availability.for_subject(subject_id, tx).await?.len() as f64;
-    let total_shifted = schedule.count_total(subject_id, tx).await? as f64;
-    let shifts_current = schedule.count(subject_id, tx).await? as f64;
+        .decay(config.scheduler.decay);
+
+    if let Some(term) = term {
+        history = history.carry_over(carry_over.unwrap_or(term.carry_over));
+    }
+
+    let problem = Problem {
+        slots,
+        availability: availability.entries(&mut *tx).await?,
+        history,
+        constraints,
+        pins,
+        inactive,
+        time_off: off,
+    };
+
+    let reasons: HashMap<Id, &Option<String>> = time_off
+        .iter()
+        .map(|time_off| (time_off.id, &time_off.reason))
+        .collect();
+
+    let withdrawn = problem
+        .withdrawn()
+        .into_iter()
+        .map(|(slot, subject, time_off)| Withdrawn {
+            slot,
+            subject,
+            time_off,
+            reason: reasons[&time_off].clone(),
+        })
+        .collect();
 
-    Ok(weeks_since - flexibility / 20.0 - total_shifted / 5.0 - (2.0 + shifts_current).powi(3))
+    Ok((problem, withdrawn))
 }
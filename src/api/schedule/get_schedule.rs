@@ -7,13 +7,15 @@ use axum::{
 use serde::Serialize;
 use souvenir::Id;
 
-use crate::{api::ApiResult, Application};
+use super::schedules::ApiSchedule;
+use crate::{api::ApiResult, Application, Availability, Schedule, Slot, Staffing};
 
 #[derive(Serialize)]
 pub struct ApiResponse {
-    pub id: Id,
-    pub parent: Option<Id>,
+    #[serde(flatten)]
+    pub schedule: ApiSchedule,
     pub assignments: HashMap<Id, Vec<ApiAssignment>>,
+    pub staffing: HashMap<Id, Staffing>,
 }
 
 #[derive(Serialize)]
@@ -27,16 +29,8 @@ pub async fn get_schedule(
     State(state): State<Application>,
     Path(id): Path<String>,
 ) -> ApiResult<ApiResponse> {
-    let metadata = sqlx::query!(
-        r#"
-            SELECT id AS "id: Id", parent_id AS "parent: Id" FROM schedule 
-                WHERE id = $1
-                LIMIT 1;
-        "#,
-        Id::parse(&id)? as Id,
-    )
-    .fetch_one(&state.pool)
-    .await?;
+    let mut conn = state.pool.acquire().await?;
+    let schedule = Schedule::resolve(Id::parse(&id)?, &mut conn).await?;
 
     let mut map: HashMap<Id, Vec<ApiAssignment>> = HashMap::new();
 
@@ -51,9 +45,9 @@ pub async fn get_schedule(
                 INNER JOIN subject ON subject_id = subject.id 
                 WHERE schedule_id = $1;
         "#,
-        metadata.id as Id,
+        schedule.id as Id,
     )
-    .fetch_all(&state.pool)
+    .fetch_all(&mut *conn)
     .await?
     .into_iter()
     .for_each(|e| {
@@ -64,9 +58,27 @@ pub async fn get_schedule(
         })
     });
 
+    // Slots of the schedule's own week, so empty ones are listed too.
+    let listed = match schedule.availability {
+        Some(id) => {
+            Availability::find(id, &mut conn)
+                .await?
+                .entries(&mut conn)
+                .await?
+        }
+        None => HashMap::new(),
+    };
+
+    let staffing = Slot::all_slots(&mut conn)
+        .await?
+        .into_iter()
+        .filter(|slot| map.contains_key(&slot.id) || listed.contains_key(&slot.id))
+        .map(|slot| (slot.id, slot.staffing(&state.config.scheduler)))
+        .collect();
+
     Ok(Json(ApiResponse {
-        id: metadata.id,
-        parent: metadata.parent,
+        schedule: schedule.into(),
         assignments: map,
+        staffing,
     }))
 }
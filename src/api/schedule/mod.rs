@@ -5,10 +5,16 @@ use axum::{
 
 use crate::Application;
 
+mod edit;
+mod explain;
 mod export;
 mod generate;
 mod get_schedule;
+mod pins;
+mod regenerate;
 mod schedules;
+mod stats;
+mod validate;
 
 pub fn create_router() -> Router<Application> {
     Router::new()
@@ -16,4 +22,13 @@ pub fn create_router() -> Router<Application> {
         .route("/schedule/generate", post(generate::generate))
         .route("/schedule/{id}", get(get_schedule::get_schedule))
         .route("/schedule/{id}/export", get(export::export))
+        .route("/schedule/{id}/explain", get(explain::explain))
+        .route("/schedule/{id}/pins", get(pins::pins).post(pins::set_pins))
+        .route("/schedule/{id}/regenerate", post(regenerate::regenerate))
+        .route("/schedule/{id}/assign", post(edit::assign))
+        .route("/schedule/{id}/unassign", post(edit::unassign))
+        .route("/schedule/{id}/swap", post(edit::swap))
+        .route("/schedule/{id}/publish", post(edit::publish))
+        .route("/schedule/{id}/validate", get(validate::validate))
+        .route("/schedule/{id}/stats", get(stats::stats))
 }
@@ -0,0 +1,92 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use souvenir::Id;
+use sqlx::PgConnection;
+
+use crate::{api::ApiResult, ApiError, Application, Schedule, Slot, Subject};
+
+/// A subject agreed to work a slot ahead of generation.
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pin {
+    pub slot: Id,
+    pub subject: Id,
+}
+
+#[derive(Deserialize)]
+pub struct PinsBody {
+    pins: Vec<Pin>,
+}
+
+/// Checks that every pin names a known slot and an active subject, and
+/// drops duplicates.
+pub async fn validate(pins: Vec<Pin>, tx: &mut PgConnection) -> Result<Vec<Pin>, ApiError> {
+    let mut valid: Vec<Pin> = Vec::new();
+
+    for pin in pins {
+        if valid.contains(&pin) {
+            continue;
+        }
+
+        if Slot::find(pin.slot, &mut *tx).await.is_err()
+            || Subject::find(pin.subject, &mut *tx).await.is_err()
+        {
+            return Err(ApiError {
+                status_code: StatusCode::BAD_REQUEST,
+                error: anyhow::anyhow!(
+                    "Pin ({}, {}) names an unknown slot or subject",
+                    pin.slot,
+                    pin.subject
+                ),
+            });
+        }
+
+        if !Subject::find(pin.subject, &mut *tx).await?.active {
+            return Err(ApiError {
+                status_code: StatusCode::BAD_REQUEST,
+                error: anyhow::anyhow!("Pin names inactive subject {}", pin.subject),
+            });
+        }
+
+        valid.push(pin);
+    }
+
+    Ok(valid)
+}
+
+pub async fn pins(State(state): State<Application>, Path(id): Path<String>) -> ApiResult<Vec<Pin>> {
+    let id = Id::parse(&id)?;
+
+    let mut conn = state.pool.acquire().await?;
+    let schedule = Schedule::resolve(id, &mut conn).await?;
+
+    Ok(Json(
+        schedule
+            .pins(&mut conn)
+            .await?
+            .into_iter()
+            .map(|(slot, subject)| Pin { slot, subject })
+            .collect(),
+    ))
+}
+
+pub async fn set_pins(
+    State(state): State<Application>,
+    Path(id): Path<String>,
+    Json(body): Json<PinsBody>,
+) -> ApiResult<Vec<Pin>> {
+    let id = Id::parse(&id)?;
+
+    let mut tx = state.pool.begin().await?;
+    let schedule = Schedule::resolve(id, &mut tx).await?;
+    let pins = validate(body.pins, &mut tx).await?;
+
+    let pairs: Vec<(Id, Id)> = pins.iter().map(|pin| (pin.slot, pin.subject)).collect();
+    schedule.set_pins(&pairs, &mut tx).await?;
+    tx.commit().await?;
+
+    Ok(Json(pins))
+}
@@ -0,0 +1,216 @@
+use std::collections::HashSet;
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use chrono::{DateTime, NaiveDate};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use souvenir::Id;
+
+use super::{
+    generate::{load_problem, respond, save, settings, GenerateResponse},
+    pins::Pin,
+};
+use crate::{api::ApiResult, ApiError, Application, Availability, Options, Schedule, Solver};
+
+#[derive(Deserialize)]
+pub struct RegenerateRequest {
+    pub name: Option<String>,
+    /// Slots to regenerate.
+    #[serde(default)]
+    pub slots: Vec<Id>,
+    /// Regenerate every slot starting on this calendar day, in the
+    /// configured timezone.
+    pub day: Option<NaiveDate>,
+    /// Subjects to leave out of the regenerated slots, such as someone
+    /// suddenly absent.
+    #[serde(default)]
+    pub absent: Vec<Id>,
+    #[serde(default)]
+    pub scoring: Value,
+    #[serde(default)]
+    pub solver: Solver,
+    #[serde(default)]
+    pub improve: bool,
+    #[serde(default)]
+    pub preview: bool,
+    #[serde(default)]
+    pub seed: u64,
+}
+
+#[derive(Serialize)]
+pub struct RegenerateResponse {
+    #[serde(flatten)]
+    pub schedule: GenerateResponse,
+    /// Slots that were generated again. Assignments to every other slot are
+    /// kept, and reported as pinned.
+    pub regenerated: Vec<Id>,
+    /// Pins on the regenerated slots that were dropped because their subject
+    /// is absent.
+    pub unpinned: Vec<Pin>,
+}
+
+/// Creates a revision of a schedule that keeps every assignment outside the
+/// selected slots and generates the selected ones again.
+pub async fn regenerate(
+    State(state): State<Application>,
+    Path(id): Path<String>,
+    Json(body): Json<RegenerateRequest>,
+) -> ApiResult<RegenerateResponse> {
+    let id = Id::parse(&id)?;
+
+    let scoring = state
+        .config
+        .scheduler
+        .scoring
+        .merge(body.scoring)
+        .map_err(|error| ApiError {
+            status_code: StatusCode::BAD_REQUEST,
+            error,
+        })?;
+
+    state
+        .config
+        .scheduler
+        .decay
+        .validate()
+        .map_err(|error| ApiError {
+            status_code: StatusCode::BAD_REQUEST,
+            error,
+        })?;
+
+    let mut tx = state.pool.begin().await?;
+
+    let original = Schedule::resolve(id, &mut tx).await?;
+
+    // Selected slots are solved again against the availability the kept
+    // assignments were made from.
+    let availability = match original.availability {
+        Some(availability) => Availability::find(availability, &mut tx).await?,
+        None => Availability::fetch_current(&mut tx).await?,
+    };
+
+    let current = Schedule::fetch_current(&mut tx).await.ok().map(|s| s.id);
+
+    // Regenerated slots weigh history the way the original run did.
+    let carry_over = original
+        .parameters
+        .as_ref()
+        .and_then(|parameters| parameters["settings"]["carry_over"].as_f64());
+
+    let mut schedule = original.revise(body.name);
+    let (mut problem, withdrawn) = load_problem(
+        &schedule,
+        &availability,
+        &[],
+        carry_over,
+        &state.config,
+        &mut tx,
+    )
+    .await?;
+
+    let offset = state.config.scheduler.utc_offset_hours as i64 * 60 * 60;
+    let on_day = |w2m_id: i32| {
+        DateTime::from_timestamp(w2m_id as i64 + offset, 0)
+            .is_some_and(|start| Some(start.date_naive()) == body.day)
+    };
+
+    let selected: HashSet<Id> = problem
+        .slots
+        .iter()
+        .filter(|slot| body.slots.contains(&slot.id) || on_day(slot.w2m_id))
+        .map(|slot| slot.id)
+        .collect();
+
+    if selected.is_empty() || body.slots.iter().any(|slot| !selected.contains(slot)) {
+        return Err(ApiError {
+            status_code: StatusCode::BAD_REQUEST,
+            error: anyhow::anyhow!("Select known slots or a day that has slots"),
+        });
+    }
+
+    let mut pins: Vec<(Id, Id)> = original
+        .assignments(&mut tx)
+        .await?
+        .into_iter()
+        .filter(|(slot, _)| !selected.contains(slot))
+        .collect();
+
+    let mut unpinned = Vec::new();
+
+    for &(slot, subject) in problem.pins.iter() {
+        if !selected.contains(&slot) {
+            continue;
+        }
+
+        if body.absent.contains(&subject) {
+            unpinned.push(Pin { slot, subject });
+        } else {
+            pins.push((slot, subject));
+        }
+    }
+
+    problem.pins = pins;
+    problem
+        .availability
+        .retain(|slot, _| selected.contains(slot));
+
+    for subjects in problem.availability.values_mut() {
+        subjects.retain(|subject| !body.absent.contains(subject));
+    }
+
+    let options = Options {
+        scoring,
+        solver: body.solver,
+        improve: body.improve,
+        seed: body.seed,
+    };
+
+    let mut regenerated: Vec<Id> = selected.iter().copied().collect();
+    regenerated.sort();
+
+    let mut settings = settings(&state.config)?;
+
+    if let Some(carry_over) = carry_over {
+        settings["carry_over"] = carry_over.into();
+    }
+
+    settings["regenerated"] = serde_json::to_value(&regenerated)?;
+    settings["absent"] = serde_json::to_value(&body.absent)?;
+    schedule.generated(&availability, &options, settings)?;
+
+    let mut outcome = problem.solve(&options);
+
+    outcome
+        .understaffed
+        .retain(|understaffed| selected.contains(&understaffed.slot));
+    outcome.unmet.retain(|unmet| selected.contains(&unmet.slot));
+
+    if !body.preview {
+        schedule.upsert(&mut tx).await?;
+        save(&schedule, &outcome, &mut tx).await?;
+
+        if current == Some(original.id) {
+            sqlx::query!("UPDATE parameters SET schedule = $1;", schedule.id as Id)
+                .execute(&mut *tx)
+                .await?;
+        }
+    }
+
+    tx.commit().await?;
+
+    let mut response = respond(schedule, body.preview, options.seed, &problem, outcome);
+    response.withdrawn = withdrawn
+        .into_iter()
+        .filter(|withdrawn| selected.contains(&withdrawn.slot))
+        .collect();
+
+    Ok(Json(RegenerateResponse {
+        schedule: response,
+        regenerated,
+        unpinned,
+    }))
+}
@@ -1,23 +1,77 @@
-use axum::{extract::State, Json};
-use serde::Serialize;
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use souvenir::Id;
 
-use crate::{api::ApiResult, Application};
+use crate::{api::ApiResult, Application, Schedule};
 
 #[derive(Serialize)]
-pub struct ApiResponse {
+pub struct ApiSchedule {
     pub id: Id,
     pub name: Option<String>,
     pub parent: Option<Id>,
+    pub revision: bool,
+    pub published_at: Option<DateTime<Utc>>,
+    pub availability: Option<Id>,
+    pub strategy: Option<String>,
+    pub parameters: Option<Value>,
+    pub algorithm_version: Option<i32>,
+    pub created_at: DateTime<Utc>,
+    pub term: Option<Id>,
 }
 
-pub async fn schedules(State(app): State<Application>) -> ApiResult<Vec<ApiResponse>> {
+impl From<Schedule> for ApiSchedule {
+    fn from(schedule: Schedule) -> Self {
+        Self {
+            id: schedule.id,
+            name: schedule.name,
+            parent: schedule.parent,
+            revision: schedule.revision,
+            published_at: schedule.published_at,
+            availability: schedule.availability,
+            strategy: schedule.strategy,
+            parameters: schedule.parameters,
+            algorithm_version: schedule.algorithm_version,
+            created_at: schedule.created_at,
+            term: schedule.term,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SchedulesQuery {
+    /// Only list schedules in this term.
+    term: Option<Id>,
+}
+
+pub async fn schedules(
+    State(app): State<Application>,
+    Query(query): Query<SchedulesQuery>,
+) -> ApiResult<Vec<ApiSchedule>> {
     let response = sqlx::query_as!(
-        ApiResponse,
+        ApiSchedule,
         r#"
-            SELECT id AS "id: Id", name, parent_id AS "parent: Id" 
-                FROM schedule;
-        "#
+            SELECT
+                id AS "id: Id",
+                name,
+                parent_id AS "parent: Id",
+                revision,
+                published_at,
+                availability_id AS "availability: Id",
+                strategy,
+                parameters,
+                algorithm_version,
+                created_at,
+                term_id AS "term: Id"
+            FROM schedule
+                WHERE $1::UUID IS NULL OR term_id = $1
+                ORDER BY created_at;
+        "#,
+        query.term as Option<Id>,
     )
     .fetch_all(&app.pool)
     .await?;
@@ -0,0 +1,55 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use serde::Serialize;
+use souvenir::Id;
+
+use crate::{api::ApiResult, Application, Schedule, Statistics, Subject, Tally};
+
+#[derive(Serialize)]
+pub struct StatsResponse {
+    pub id: Id,
+    /// Subjects the statistics are taken over.
+    pub subjects: u32,
+    #[serde(flatten)]
+    pub statistics: Statistics,
+}
+
+/// Measures how evenly a schedule and its ancestry spread shifts across
+/// every active subject.
+pub async fn stats(
+    State(state): State<Application>,
+    Path(id): Path<String>,
+) -> ApiResult<StatsResponse> {
+    let id = Id::parse(&id)?;
+
+    let mut conn = state.pool.acquire().await?;
+
+    let schedule = Schedule::resolve(id, &mut conn).await?;
+    let history = schedule.history(&mut conn).await?;
+    let assignments = schedule.assignments(&mut conn).await?;
+
+    let tallies: Vec<(Id, Tally)> = Subject::all_subjects(&mut conn)
+        .await?
+        .into_iter()
+        .filter(|subject| subject.active)
+        .map(|subject| {
+            let tally = Tally {
+                current: assignments
+                    .iter()
+                    .filter(|&&(_, s)| s == subject.id)
+                    .count() as u32,
+                total: history.total(subject.id),
+            };
+
+            (subject.id, tally)
+        })
+        .collect();
+
+    Ok(Json(StatsResponse {
+        id: schedule.id,
+        subjects: tallies.len() as u32,
+        statistics: Statistics::measure(&tallies),
+    }))
+}
@@ -0,0 +1,69 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use serde::Serialize;
+use souvenir::Id;
+
+use super::{generate::load_problem, pins::Pin};
+use crate::{api::ApiResult, Application, Availability, Finding, Schedule, Severity};
+
+#[derive(Serialize)]
+pub struct ValidateResponse {
+    pub id: Id,
+    /// Availability the schedule was checked against: the one it was
+    /// generated from, or the current one.
+    pub availability: Id,
+    /// Whether no finding is an error.
+    pub valid: bool,
+    pub findings: Vec<Finding>,
+}
+
+/// Checks a schedule's assignments, however they were made, against
+/// availability, staffing and every constraint generation honours.
+pub async fn validate(
+    State(state): State<Application>,
+    Path(id): Path<String>,
+) -> ApiResult<ValidateResponse> {
+    let id = Id::parse(&id)?;
+
+    let mut tx = state.pool.begin().await?;
+
+    let schedule = Schedule::resolve(id, &mut tx).await?;
+    let availability = match schedule.availability {
+        Some(availability) => Availability::find(availability, &mut tx).await?,
+        None => Availability::fetch_current(&mut tx).await?,
+    };
+
+    // Pins the schedule was generated with, as recorded in its parameters.
+    // Schedules that predate recording them fall back to the pins of the
+    // schedule they follow.
+    let recorded: Option<Vec<Pin>> = schedule
+        .parameters
+        .as_ref()
+        .and_then(|parameters| serde_json::from_value(parameters["settings"]["pins"].clone()).ok());
+    let pinned: Vec<Id> = recorded.iter().flatten().map(|pin| pin.slot).collect();
+
+    let (mut problem, _) = load_problem(
+        &schedule,
+        &availability,
+        &pinned,
+        None,
+        &state.config,
+        &mut tx,
+    )
+    .await?;
+
+    if let Some(recorded) = recorded {
+        problem.pins = recorded.iter().map(|pin| (pin.slot, pin.subject)).collect();
+    }
+
+    let findings = problem.validate(&schedule.assignments(&mut tx).await?);
+
+    Ok(Json(ValidateResponse {
+        id: schedule.id,
+        availability: availability.id,
+        valid: findings.iter().all(|f| f.severity != Severity::Error),
+        findings,
+    }))
+}
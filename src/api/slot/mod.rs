@@ -1,52 +1,179 @@
 use axum::{
-    extract::{Path, State},
-    routing::get,
+    extract::{Path, Query, State},
+    routing::{get, post},
     Json, Router,
 };
-use serde::Serialize;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
 use souvenir::Id;
 
-use crate::{ApiResult, Application, Slot};
+use crate::{
+    ApiError, ApiResult, Application, Availability, Role, RoleRequirement, Slot, Staffing,
+};
 
 #[derive(Clone, Serialize)]
 struct ApiSlot {
     id: Id,
     w2m_id: Option<i32>,
+    min_staff: Option<i32>,
+    max_staff: Option<i32>,
+    staffing: Staffing,
+}
+
+impl ApiSlot {
+    fn from(slot: Slot, state: &Application) -> Self {
+        Self {
+            id: slot.id,
+            w2m_id: Some(slot.w2m_id),
+            min_staff: slot.min_staff,
+            max_staff: slot.max_staff,
+            staffing: slot.staffing(&state.config.scheduler),
+        }
+    }
+}
+
+#[derive(Clone, Deserialize)]
+struct SlotsQuery {
+    /// Only the slots someone marked themselves available for in this
+    /// availability.
+    availability: Option<Id>,
 }
 
-async fn slots(State(state): State<Application>) -> ApiResult<Vec<ApiSlot>> {
+async fn slots(
+    State(state): State<Application>,
+    Query(query): Query<SlotsQuery>,
+) -> ApiResult<Vec<ApiSlot>> {
     let mut conn = state.pool.acquire().await?;
-    let result = Slot::all_slots(&mut conn).await?;
+    let result = match query.availability {
+        Some(id) => {
+            Availability::find(id, &mut conn)
+                .await?
+                .slots(&mut conn)
+                .await?
+        }
+        None => Slot::all_slots(&mut conn).await?,
+    };
 
     Ok(Json(
         result
             .into_iter()
-            .map(|Slot { id, w2m_id }| ApiSlot {
-                id,
-                w2m_id: Some(w2m_id),
-            })
+            .map(|slot| ApiSlot::from(slot, &state))
             .collect(),
     ))
 }
 
 async fn slot(State(state): State<Application>, Path(id): Path<String>) -> ApiResult<ApiSlot> {
     let id = Id::parse(&id)?;
-    let result = sqlx::query_as!(
-        ApiSlot,
-        r#"
-            SELECT id as "id: Id", w2m_id FROM slot
-                WHERE id = $1;
-        "#,
-        id as Id,
-    )
-    .fetch_one(&state.pool)
-    .await?;
 
-    Ok(Json(result))
+    let mut conn = state.pool.acquire().await?;
+    let result = Slot::find(id, &mut conn).await?;
+
+    Ok(Json(ApiSlot::from(result, &state)))
+}
+
+#[derive(Clone, Deserialize)]
+struct StaffingBody {
+    min_staff: Option<i32>,
+    max_staff: Option<i32>,
+}
+
+async fn set_staffing(
+    State(state): State<Application>,
+    Path(id): Path<String>,
+    Json(body): Json<StaffingBody>,
+) -> ApiResult<ApiSlot> {
+    let id = Id::parse(&id)?;
+
+    let invalid = match (body.min_staff, body.max_staff) {
+        (Some(min), _) if min < 0 => true,
+        (_, Some(max)) if max < 0 => true,
+        (Some(min), Some(max)) => min > max,
+        _ => false,
+    };
+
+    if invalid {
+        return Err(ApiError {
+            status_code: StatusCode::BAD_REQUEST,
+            error: anyhow::anyhow!("Staffing must be non-negative with min_staff <= max_staff"),
+        });
+    }
+
+    let mut conn = state.pool.acquire().await?;
+    let mut slot = Slot::find(id, &mut conn).await?;
+    slot.set_staffing(body.min_staff, body.max_staff, &mut conn)
+        .await?;
+
+    Ok(Json(ApiSlot::from(slot, &state)))
+}
+
+async fn requirements(
+    State(state): State<Application>,
+    Path(id): Path<String>,
+) -> ApiResult<Vec<RoleRequirement>> {
+    let id = Id::parse(&id)?;
+
+    let mut conn = state.pool.acquire().await?;
+    let slot = Slot::find(id, &mut conn).await?;
+
+    Ok(Json(slot.requirements(&mut conn).await?))
+}
+
+#[derive(Clone, Deserialize)]
+struct RequirementEntry {
+    role: Id,
+    count: u32,
+}
+
+#[derive(Clone, Deserialize)]
+struct RequirementsBody {
+    requirements: Vec<RequirementEntry>,
+}
+
+async fn set_requirements(
+    State(state): State<Application>,
+    Path(id): Path<String>,
+    Json(body): Json<RequirementsBody>,
+) -> ApiResult<Vec<RoleRequirement>> {
+    let id = Id::parse(&id)?;
+
+    let mut tx = state.pool.begin().await?;
+    let slot = Slot::find(id, &mut tx).await?;
+    let mut requirements: Vec<RoleRequirement> = Vec::new();
+
+    for entry in body.requirements {
+        let invalid = !(1..=i32::MAX as u32).contains(&entry.count)
+            || requirements.iter().any(|r| r.role == entry.role)
+            || Role::find(entry.role, &mut tx).await.is_err();
+
+        if invalid {
+            return Err(ApiError {
+                status_code: StatusCode::BAD_REQUEST,
+                error: anyhow::anyhow!(
+                    "Requirements must name distinct known roles with a count between 1 and {}",
+                    i32::MAX
+                ),
+            });
+        }
+
+        requirements.push(RoleRequirement {
+            role: entry.role,
+            count: entry.count,
+        });
+    }
+
+    slot.set_requirements(&requirements, &mut tx).await?;
+    tx.commit().await?;
+
+    Ok(Json(requirements))
 }
 
 pub fn create_router() -> Router<Application> {
     Router::new()
         .route("/slots", get(slots))
         .route("/slot/{id}", get(slot))
+        .route("/slot/{id}/staffing", post(set_staffing))
+        .route(
+            "/slot/{id}/requirements",
+            get(requirements).post(set_requirements),
+        )
 }
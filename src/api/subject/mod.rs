@@ -1,29 +1,58 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     routing::{get, post},
     Json, Router,
 };
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use souvenir::Id;
 
-use crate::{ApiResult, Application, Subject};
+use crate::{api::role::ApiRole, ApiError, ApiResult, Application, Role, Subject, Targets};
 
 #[derive(Clone, Serialize)]
 struct ApiSubject {
     id: Id,
     tag: String,
     name: Option<String>,
+    max_shifts_per_week: Option<i32>,
+    active: bool,
+    joined_at: Option<DateTime<Utc>>,
+    left_at: Option<DateTime<Utc>>,
 }
 
-async fn subjects(State(state): State<Application>) -> ApiResult<Vec<ApiSubject>> {
+impl From<Subject> for ApiSubject {
+    fn from(subject: Subject) -> Self {
+        Self {
+            id: subject.id,
+            tag: subject.tag,
+            name: subject.name,
+            max_shifts_per_week: subject.max_shifts_per_week,
+            active: subject.active,
+            joined_at: subject.joined_at,
+            left_at: subject.left_at,
+        }
+    }
+}
+
+#[derive(Clone, Deserialize)]
+struct SubjectsQuery {
+    active: Option<bool>,
+}
+
+async fn subjects(
+    State(state): State<Application>,
+    Query(query): Query<SubjectsQuery>,
+) -> ApiResult<Vec<ApiSubject>> {
     let mut conn = state.pool.acquire().await?;
     let result = Subject::all_subjects(&mut conn).await?;
 
     Ok(Json(
         result
             .into_iter()
-            .map(|Subject { id, tag, name }| ApiSubject { id, tag, name })
+            .filter(|subject| query.active.is_none_or(|active| subject.active == active))
+            .map(ApiSubject::from)
             .collect(),
     ))
 }
@@ -35,9 +64,61 @@ async fn subject(
     let id = Id::parse(&id)?;
 
     let mut conn = state.pool.acquire().await?;
-    Ok(Json(Subject::find(id, &mut conn).await.map(
-        |Subject { id, tag, name }| ApiSubject { id, tag, name },
-    )?))
+    Ok(Json(
+        Subject::find(id, &mut conn).await.map(ApiSubject::from)?,
+    ))
+}
+
+#[derive(Clone, Deserialize)]
+struct LimitsBody {
+    max_shifts_per_week: Option<i32>,
+}
+
+async fn set_limits(
+    State(state): State<Application>,
+    Path(id): Path<String>,
+    Json(body): Json<LimitsBody>,
+) -> ApiResult<ApiSubject> {
+    let id = Id::parse(&id)?;
+
+    if body.max_shifts_per_week.is_some_and(|max| max < 0) {
+        return Err(ApiError {
+            status_code: StatusCode::BAD_REQUEST,
+            error: anyhow::anyhow!("max_shifts_per_week must be non-negative"),
+        });
+    }
+
+    let mut conn = state.pool.acquire().await?;
+    let mut subject = Subject::find(id, &mut conn).await?;
+    subject
+        .set_max_shifts(body.max_shifts_per_week, &mut conn)
+        .await?;
+
+    Ok(Json(ApiSubject::from(subject)))
+}
+
+async fn set_active(state: Application, id: String, active: bool) -> ApiResult<ApiSubject> {
+    let id = Id::parse(&id)?;
+
+    let mut conn = state.pool.acquire().await?;
+    let mut subject = Subject::find(id, &mut conn).await?;
+    subject.set_active(active, &mut conn).await?;
+
+    Ok(Json(ApiSubject::from(subject)))
+}
+
+async fn deactivate(
+    State(state): State<Application>,
+    Path(id): Path<String>,
+) -> ApiResult<ApiSubject> {
+    set_active(state, id, false).await
+}
+
+async fn reactivate(
+    State(state): State<Application>,
+    Path(id): Path<String>,
+) -> ApiResult<ApiSubject> {
+    set_active(state, id, true).await
 }
 
 #[derive(Clone, Deserialize)]
@@ -63,9 +144,155 @@ async fn associate(
     Ok(Json(json!({ "status": "ok" })))
 }
 
+async fn roles(
+    State(state): State<Application>,
+    Path(id): Path<String>,
+) -> ApiResult<Vec<ApiRole>> {
+    let id = Id::parse(&id)?;
+
+    let mut conn = state.pool.acquire().await?;
+    let subject = Subject::find(id, &mut conn).await?;
+
+    Ok(Json(
+        subject
+            .roles(&mut conn)
+            .await?
+            .into_iter()
+            .map(ApiRole::from)
+            .collect(),
+    ))
+}
+
+#[derive(Clone, Deserialize)]
+struct RolesBody {
+    roles: Vec<Id>,
+}
+
+async fn set_roles(
+    State(state): State<Application>,
+    Path(id): Path<String>,
+    Json(body): Json<RolesBody>,
+) -> ApiResult<Vec<ApiRole>> {
+    let id = Id::parse(&id)?;
+
+    let mut tx = state.pool.begin().await?;
+    let subject = Subject::find(id, &mut tx).await?;
+
+    for &role in body.roles.iter() {
+        if Role::find(role, &mut tx).await.is_err() {
+            return Err(ApiError {
+                status_code: StatusCode::BAD_REQUEST,
+                error: anyhow::anyhow!("Unknown role {role}"),
+            });
+        }
+    }
+
+    let mut roles = body.roles;
+    roles.sort();
+    roles.dedup();
+
+    subject.set_roles(&roles, &mut tx).await?;
+    let roles = subject.roles(&mut tx).await?;
+    tx.commit().await?;
+
+    Ok(Json(roles.into_iter().map(ApiRole::from).collect()))
+}
+
+/// Shift targets of a subject. Unset fields carry no target.
+#[derive(Clone, Serialize, Deserialize)]
+struct ApiTargets {
+    weekly_min: Option<i32>,
+    weekly_max: Option<i32>,
+    term_min: Option<i32>,
+    term_max: Option<i32>,
+}
+
+impl From<Targets> for ApiTargets {
+    fn from(targets: Targets) -> Self {
+        Self {
+            weekly_min: targets.weekly_min,
+            weekly_max: targets.weekly_max,
+            term_min: targets.term_min,
+            term_max: targets.term_max,
+        }
+    }
+}
+
+impl ApiTargets {
+    fn validate(&self) -> Result<(), ApiError> {
+        let values = [
+            self.weekly_min,
+            self.weekly_max,
+            self.term_min,
+            self.term_max,
+        ];
+        let inverted =
+            |min: Option<i32>, max: Option<i32>| min.zip(max).is_some_and(|(a, b)| a > b);
+
+        let error = if values.iter().flatten().any(|&value| value < 0) {
+            Some(anyhow::anyhow!("Targets must be non-negative"))
+        } else if inverted(self.weekly_min, self.weekly_max)
+            || inverted(self.term_min, self.term_max)
+        {
+            Some(anyhow::anyhow!(
+                "A minimum target cannot exceed its maximum"
+            ))
+        } else {
+            None
+        };
+
+        match error {
+            Some(error) => Err(ApiError {
+                status_code: StatusCode::BAD_REQUEST,
+                error,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+async fn targets(
+    State(state): State<Application>,
+    Path(id): Path<String>,
+) -> ApiResult<ApiTargets> {
+    let id = Id::parse(&id)?;
+
+    let mut conn = state.pool.acquire().await?;
+    let subject = Subject::find(id, &mut conn).await?;
+
+    Ok(Json(Targets::find(subject.id, &mut conn).await?.into()))
+}
+
+async fn set_targets(
+    State(state): State<Application>,
+    Path(id): Path<String>,
+    Json(body): Json<ApiTargets>,
+) -> ApiResult<ApiTargets> {
+    let id = Id::parse(&id)?;
+    body.validate()?;
+
+    let mut conn = state.pool.acquire().await?;
+    let subject = Subject::find(id, &mut conn).await?;
+
+    let targets = Targets {
+        weekly_min: body.weekly_min,
+        weekly_max: body.weekly_max,
+        term_min: body.term_min,
+        term_max: body.term_max,
+    };
+    targets.set(subject.id, &mut conn).await?;
+
+    Ok(Json(targets.into()))
+}
+
 pub fn create_router() -> Router<Application> {
     Router::new()
         .route("/subjects", get(subjects))
         .route("/subject/{id}", get(subject))
+        .route("/subject/{id}/limits", post(set_limits))
+        .route("/subject/{id}/deactivate", post(deactivate))
+        .route("/subject/{id}/reactivate", post(reactivate))
+        .route("/subject/{id}/roles", get(roles).post(set_roles))
+        .route("/subject/{id}/targets", get(targets).post(set_targets))
         .route("/subjects/associate", post(associate))
 }
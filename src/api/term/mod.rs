@@ -0,0 +1,212 @@
+use std::collections::{HashMap, HashSet};
+
+use axum::{
+    extract::{Path, State},
+    routing::get,
+    Json, Router,
+};
+use chrono::NaiveDate;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use souvenir::Id;
+
+use crate::{ApiError, ApiResult, Application, Schedule, Statistics, Subject, Tally, Term};
+
+#[derive(Clone, Serialize)]
+struct ApiTerm {
+    id: Id,
+    name: String,
+    starts_on: NaiveDate,
+    ends_on: NaiveDate,
+    carry_over: f64,
+}
+
+impl From<Term> for ApiTerm {
+    fn from(term: Term) -> Self {
+        Self {
+            id: term.id,
+            name: term.name,
+            starts_on: term.starts_on,
+            ends_on: term.ends_on,
+            carry_over: term.carry_over,
+        }
+    }
+}
+
+async fn terms(State(state): State<Application>) -> ApiResult<Vec<ApiTerm>> {
+    let mut conn = state.pool.acquire().await?;
+    let result = Term::all_terms(&mut conn).await?;
+
+    Ok(Json(result.into_iter().map(ApiTerm::from).collect()))
+}
+
+async fn term(State(state): State<Application>, Path(id): Path<String>) -> ApiResult<ApiTerm> {
+    let id = Id::parse(&id)?;
+
+    let mut conn = state.pool.acquire().await?;
+    Ok(Json(Term::find(id, &mut conn).await?.into()))
+}
+
+#[derive(Clone, Deserialize)]
+struct TermBody {
+    name: String,
+    starts_on: NaiveDate,
+    ends_on: NaiveDate,
+    #[serde(default)]
+    carry_over: f64,
+}
+
+impl TermBody {
+    fn validate(&self) -> Result<(), ApiError> {
+        let error = if self.name.trim().is_empty() {
+            Some(anyhow::anyhow!("Term names must be non-empty"))
+        } else if self.starts_on > self.ends_on {
+            Some(anyhow::anyhow!("A term cannot end before it starts"))
+        } else if !(0.0..=1.0).contains(&self.carry_over) {
+            Some(anyhow::anyhow!("carry_over must be between 0 and 1"))
+        } else {
+            None
+        };
+
+        match error {
+            Some(error) => Err(ApiError {
+                status_code: StatusCode::BAD_REQUEST,
+                error,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+async fn create(
+    State(state): State<Application>,
+    Json(body): Json<TermBody>,
+) -> ApiResult<ApiTerm> {
+    body.validate()?;
+
+    let term = Term::new(
+        body.name.trim().to_string(),
+        body.starts_on,
+        body.ends_on,
+        body.carry_over,
+    );
+
+    let mut conn = state.pool.acquire().await?;
+    term.upsert(&mut conn).await?;
+
+    Ok(Json(term.into()))
+}
+
+async fn update(
+    State(state): State<Application>,
+    Path(id): Path<String>,
+    Json(body): Json<TermBody>,
+) -> ApiResult<ApiTerm> {
+    let id = Id::parse(&id)?;
+    body.validate()?;
+
+    let mut conn = state.pool.acquire().await?;
+
+    let mut term = Term::find(id, &mut conn).await?;
+    term.name = body.name.trim().to_string();
+    term.starts_on = body.starts_on;
+    term.ends_on = body.ends_on;
+    term.carry_over = body.carry_over;
+    term.upsert(&mut conn).await?;
+
+    Ok(Json(term.into()))
+}
+
+async fn delete(State(state): State<Application>, Path(id): Path<String>) -> ApiResult<Value> {
+    let id = Id::parse(&id)?;
+
+    let mut conn = state.pool.acquire().await?;
+    Term::find(id, &mut conn).await?.delete(&mut conn).await?;
+
+    Ok(Json(json!({ "status": "ok" })))
+}
+
+/// How a term's shifts were spread across active subjects. Only the term's
+/// schedules along the current schedule's ancestry are counted. `double_shifts` counts subjects who held
+/// more than one shift in any single schedule of the term.
+#[derive(Serialize)]
+struct TermReport {
+    #[serde(flatten)]
+    term: ApiTerm,
+    schedules: u32,
+    subjects: u32,
+    #[serde(flatten)]
+    statistics: Statistics,
+}
+
+async fn report(State(state): State<Application>, Path(id): Path<String>) -> ApiResult<TermReport> {
+    let id = Id::parse(&id)?;
+
+    let mut conn = state.pool.acquire().await?;
+
+    let term = Term::find(id, &mut conn).await?;
+    let assignments = match Schedule::fetch_current(&mut conn).await.ok() {
+        Some(current) => term.assignments(&current, &mut conn).await?,
+        None => Vec::new(),
+    };
+
+    let mut per_schedule: HashMap<(Id, Id), u32> = HashMap::new();
+
+    for &(schedule, _, subject) in assignments.iter() {
+        *per_schedule.entry((schedule, subject)).or_default() += 1;
+    }
+
+    let schedules: HashSet<Id> = assignments
+        .iter()
+        .map(|&(schedule, _, _)| schedule)
+        .collect();
+
+    let subjects: Vec<Id> = Subject::all_subjects(&mut conn)
+        .await?
+        .into_iter()
+        .filter(|subject| subject.active)
+        .map(|subject| subject.id)
+        .collect();
+
+    let counts = |subject: Id| {
+        per_schedule
+            .iter()
+            .filter(move |((_, s), _)| *s == subject)
+            .map(|(_, &count)| count)
+    };
+
+    // The term spans several schedules, so only totals are tallied and
+    // double shifts are counted within each of them.
+    let tallies: Vec<(Id, Tally)> = subjects
+        .iter()
+        .map(|&subject| {
+            let tally = Tally {
+                total: counts(subject).sum(),
+                ..Default::default()
+            };
+
+            (subject, tally)
+        })
+        .collect();
+
+    let mut statistics = Statistics::measure(&tallies);
+    statistics.fairness.double_shifts = subjects
+        .iter()
+        .filter(|&&subject| counts(subject).any(|count| count > 1))
+        .count() as u32;
+
+    Ok(Json(TermReport {
+        term: term.into(),
+        schedules: schedules.len() as u32,
+        subjects: tallies.len() as u32,
+        statistics,
+    }))
+}
+
+pub fn create_router() -> Router<Application> {
+    Router::new()
+        .route("/terms", get(terms).post(create))
+        .route("/term/{id}", get(term).post(update).delete(delete))
+        .route("/term/{id}/report", get(report))
+}
@@ -0,0 +1,157 @@
+use axum::{
+    extract::{Path, Query, State},
+    routing::get,
+    Json, Router,
+};
+use chrono::NaiveDate;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use souvenir::Id;
+use sqlx::PgConnection;
+
+use crate::{ApiError, ApiResult, Application, Subject, TimeOff};
+
+#[derive(Clone, Serialize)]
+struct ApiTimeOff {
+    id: Id,
+    subject: Id,
+    starts_on: NaiveDate,
+    ends_on: NaiveDate,
+    reason: Option<String>,
+}
+
+impl From<TimeOff> for ApiTimeOff {
+    fn from(time_off: TimeOff) -> Self {
+        Self {
+            id: time_off.id,
+            subject: time_off.subject,
+            starts_on: time_off.starts_on,
+            ends_on: time_off.ends_on,
+            reason: time_off.reason,
+        }
+    }
+}
+
+#[derive(Clone, Deserialize)]
+struct TimeOffQuery {
+    subject: Option<Id>,
+}
+
+async fn all_time_off(
+    State(state): State<Application>,
+    Query(query): Query<TimeOffQuery>,
+) -> ApiResult<Vec<ApiTimeOff>> {
+    let mut conn = state.pool.acquire().await?;
+    let result = TimeOff::all_time_off(&mut conn).await?;
+
+    Ok(Json(
+        result
+            .into_iter()
+            .filter(|time_off| {
+                query
+                    .subject
+                    .is_none_or(|subject| time_off.subject == subject)
+            })
+            .map(ApiTimeOff::from)
+            .collect(),
+    ))
+}
+
+async fn time_off(
+    State(state): State<Application>,
+    Path(id): Path<String>,
+) -> ApiResult<ApiTimeOff> {
+    let id = Id::parse(&id)?;
+
+    let mut conn = state.pool.acquire().await?;
+    Ok(Json(TimeOff::find(id, &mut conn).await?.into()))
+}
+
+#[derive(Clone, Deserialize)]
+struct TimeOffBody {
+    subject: Id,
+    starts_on: NaiveDate,
+    ends_on: NaiveDate,
+    reason: Option<String>,
+}
+
+impl TimeOffBody {
+    async fn validate(&self, tx: &mut PgConnection) -> Result<(), ApiError> {
+        let error = if self.starts_on > self.ends_on {
+            Some(anyhow::anyhow!("Time off cannot end before it starts"))
+        } else if Subject::find(self.subject, tx).await.is_err() {
+            Some(anyhow::anyhow!("Unknown subject {}", self.subject))
+        } else {
+            None
+        };
+
+        match error {
+            Some(error) => Err(ApiError {
+                status_code: StatusCode::BAD_REQUEST,
+                error,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// The reason, trimmed, or none if blank.
+    fn reason(&self) -> Option<String> {
+        self.reason
+            .as_deref()
+            .map(str::trim)
+            .filter(|reason| !reason.is_empty())
+            .map(str::to_string)
+    }
+}
+
+async fn create(
+    State(state): State<Application>,
+    Json(body): Json<TimeOffBody>,
+) -> ApiResult<ApiTimeOff> {
+    let mut conn = state.pool.acquire().await?;
+    body.validate(&mut conn).await?;
+
+    let time_off = TimeOff::new(body.subject, body.starts_on, body.ends_on, body.reason());
+    time_off.upsert(&mut conn).await?;
+
+    Ok(Json(time_off.into()))
+}
+
+async fn update(
+    State(state): State<Application>,
+    Path(id): Path<String>,
+    Json(body): Json<TimeOffBody>,
+) -> ApiResult<ApiTimeOff> {
+    let id = Id::parse(&id)?;
+
+    let mut conn = state.pool.acquire().await?;
+    body.validate(&mut conn).await?;
+
+    let mut time_off = TimeOff::find(id, &mut conn).await?;
+    time_off.subject = body.subject;
+    time_off.starts_on = body.starts_on;
+    time_off.ends_on = body.ends_on;
+    time_off.reason = body.reason();
+    time_off.upsert(&mut conn).await?;
+
+    Ok(Json(time_off.into()))
+}
+
+async fn delete(State(state): State<Application>, Path(id): Path<String>) -> ApiResult<Value> {
+    let id = Id::parse(&id)?;
+
+    let mut conn = state.pool.acquire().await?;
+    TimeOff::find(id, &mut conn)
+        .await?
+        .delete(&mut conn)
+        .await?;
+
+    Ok(Json(json!({ "status": "ok" })))
+}
+
+pub fn create_router() -> Router<Application> {
+    Router::new()
+        .route("/time-off", get(all_time_off).post(create))
+        .route("/time-off/{id}", get(time_off).post(update).delete(delete))
+}
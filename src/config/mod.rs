@@ -4,10 +4,12 @@ use serde::{Deserialize, Serialize};
 pub use app::*;
 pub use database::*;
 pub use http::*;
+pub use scheduler::*;
 
 mod app;
 mod database;
 mod http;
+mod scheduler;
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -15,6 +17,8 @@ pub struct Config {
     #[serde(default = "Default::default")]
     pub http: HttpConfig,
     pub database: DatabaseConfig,
+    #[serde(default = "Default::default")]
+    pub scheduler: SchedulerConfig,
 }
 
 impl Config {
@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Decay, Scoring};
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SchedulerConfig {
+    /// Headcount below which a slot is considered understaffed.
+    pub min_staff: u32,
+    /// Headcount the generator fills a slot up to when availability allows.
+    pub max_staff: u32,
+    /// Most shifts any subject may hold in one schedule, unless the subject
+    /// sets its own limit.
+    pub max_shifts_per_week: Option<u32>,
+    /// Most shifts a subject may hold on one calendar day.
+    pub max_shifts_per_day: Option<u32>,
+    /// Least number of hours between the end of one of a subject's shifts
+    /// and the start of their next.
+    pub min_rest_hours: u32,
+    /// Offset from UTC, in hours, of the timezone calendar days are counted
+    /// in.
+    pub utc_offset_hours: i32,
+    /// Score bonus for a slot right before or after one the subject already
+    /// holds. When positive, back-to-back shifts form a block that
+    /// `min_rest_hours` does not split.
+    pub contiguous_bonus: f64,
+    /// Score bonus for a subject below their shift target, taken away
+    /// instead once they reach it.
+    pub target_weight: f64,
+    /// Largest score the generation seed adds to a candidate, so that
+    /// different seeds settle near-ties differently.
+    pub jitter: f64,
+    /// How much older schedules count towards a subject's shift history.
+    pub decay: Decay,
+    /// Strategy used to rank candidates unless a request picks its own.
+    pub scoring: Scoring,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            min_staff: 2,
+            max_staff: 2,
+            max_shifts_per_week: None,
+            max_shifts_per_day: None,
+            min_rest_hours: 0,
+            utc_offset_hours: 0,
+            contiguous_bonus: 0.0,
+            target_weight: 25.0,
+            jitter: 0.1,
+            decay: Decay::default(),
+            scoring: Scoring::default(),
+        }
+    }
+}
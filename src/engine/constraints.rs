@@ -0,0 +1,344 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use souvenir::Id;
+
+use super::problem::tiebreak;
+use crate::{Pairing, ShiftTarget};
+
+/// Length of every slot, in seconds.
+pub const SLOT_LENGTH: i64 = 60 * 60;
+
+const DAY_LENGTH: i64 = 24 * 60 * 60;
+
+/// Hard limits the generator must never break, and soft preferences it
+/// weighs alongside the scoring strategy.
+#[derive(Debug, Clone, Default)]
+pub struct Constraints {
+    /// Most shifts any subject may hold in the schedule.
+    pub max_shifts: Option<u32>,
+    /// Per-subject overrides of `max_shifts`.
+    pub subject_max_shifts: HashMap<Id, u32>,
+    /// Most shifts a subject may hold on one calendar day.
+    pub max_shifts_per_day: Option<u32>,
+    /// Least time, in seconds, between the end of one of a subject's shifts
+    /// and the start of the next.
+    pub min_rest: i64,
+    /// Offset from UTC, in seconds, of the timezone calendar days are taken
+    /// in.
+    pub utc_offset: i64,
+    /// Score added to a candidate for a slot adjacent to one they already
+    /// hold. When positive, adjacent shifts form a block and `min_rest` only
+    /// applies between blocks.
+    pub contiguous_bonus: f64,
+    /// Rules on which subjects may share a slot.
+    pub pairings: Vec<Pairing>,
+    /// Roles held by each subject.
+    pub roles: HashMap<Id, Vec<Id>>,
+    /// Shifts each subject should hold, where set.
+    pub targets: HashMap<Id, ShiftTarget>,
+    /// Score added to a candidate below their target, or taken away from one
+    /// at or past it.
+    pub target_weight: f64,
+    /// Largest score added to a candidate by [`Self::perturbation`].
+    pub jitter: f64,
+    /// Seed the perturbation is drawn from.
+    pub seed: u64,
+}
+
+/// Why an available subject could not be given a slot.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Exclusion {
+    /// The subject already holds as many shifts as they are allowed.
+    ShiftCap,
+    /// The subject already holds as many shifts as they are allowed that day.
+    DayCap,
+    /// The slot starts too soon before or after another of their shifts.
+    RestGap,
+    /// None of the subjects they must be paired with holds the slot.
+    MustPair,
+    /// Someone they must never be paired with holds the slot.
+    NeverPair,
+}
+
+impl Exclusion {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Exclusion::ShiftCap => "shift_cap",
+            Exclusion::DayCap => "day_cap",
+            Exclusion::RestGap => "rest_gap",
+            Exclusion::MustPair => "must_pair",
+            Exclusion::NeverPair => "never_pair",
+        }
+    }
+}
+
+/// Start times of the shifts each subject holds so far.
+#[derive(Debug, Clone, Default)]
+pub struct Ledger {
+    shifts: HashMap<Id, Vec<i64>>,
+}
+
+impl Ledger {
+    pub fn add(&mut self, subject: Id, start: i64) {
+        self.shifts.entry(subject).or_default().push(start);
+    }
+
+    pub fn remove(&mut self, subject: Id, start: i64) {
+        if let Some(starts) = self.shifts.get_mut(&subject) {
+            if let Some(position) = starts.iter().position(|&s| s == start) {
+                starts.swap_remove(position);
+            }
+        }
+    }
+
+    pub fn starts(&self, subject: Id) -> &[i64] {
+        self.shifts.get(&subject).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn count(&self, subject: Id) -> u32 {
+        self.starts(subject).len() as u32
+    }
+}
+
+impl Constraints {
+    /// Most shifts a subject may hold, if limited.
+    pub fn shift_cap(&self, subject: Id) -> Option<u32> {
+        self.subject_max_shifts
+            .get(&subject)
+            .copied()
+            .or(self.max_shifts)
+    }
+
+    fn day(&self, start: i64) -> i64 {
+        (start + self.utc_offset).div_euclid(DAY_LENGTH)
+    }
+
+    /// First and last start of the block a shift starting at `start` would
+    /// join. Without a contiguity bonus, every shift is a block of its own.
+    fn block(&self, start: i64, starts: &[i64]) -> (i64, i64) {
+        if self.contiguous_bonus <= 0.0 {
+            return (start, start);
+        }
+
+        let (mut first, mut last) = (start, start);
+
+        while starts.contains(&(first - SLOT_LENGTH)) {
+            first -= SLOT_LENGTH;
+        }
+
+        while starts.contains(&(last + SLOT_LENGTH)) {
+            last += SLOT_LENGTH;
+        }
+
+        (first, last)
+    }
+
+    /// Whether a shift starting at `start` leaves too little rest before or
+    /// after the other `starts`, measured between the blocks they form.
+    fn crowded(&self, start: i64, starts: &[i64]) -> bool {
+        let (first, last) = self.block(start, starts);
+
+        starts.contains(&start)
+            || starts.iter().any(|&s| {
+                let gap = if s > last {
+                    s - last
+                } else if s < first {
+                    first - s
+                } else {
+                    return false;
+                };
+
+                gap - SLOT_LENGTH < self.min_rest
+            })
+    }
+
+    /// Whether the shifts a subject holds in `ledger` all keep the rest gap
+    /// between each other. Taking a shift out of the middle of a block can
+    /// leave the pieces too close together.
+    pub fn rested(&self, subject: Id, ledger: &Ledger) -> bool {
+        let starts = ledger.starts(subject);
+
+        (0..starts.len()).all(|i| {
+            let mut others = starts.to_vec();
+            let start = others.swap_remove(i);
+
+            !self.crowded(start, &others)
+        })
+    }
+
+    /// Checks whether a subject holding the shifts in `ledger` may also take
+    /// the slot starting at `start`.
+    pub fn admits(&self, subject: Id, start: i64, ledger: &Ledger) -> Result<(), Exclusion> {
+        let starts = ledger.starts(subject);
+
+        if self
+            .shift_cap(subject)
+            .is_some_and(|cap| starts.len() as u32 >= cap)
+        {
+            return Err(Exclusion::ShiftCap);
+        }
+
+        if let Some(cap) = self.max_shifts_per_day {
+            let day = self.day(start);
+
+            if starts.iter().filter(|&&s| self.day(s) == day).count() as u32 >= cap {
+                return Err(Exclusion::DayCap);
+            }
+        }
+
+        if self.crowded(start, starts) {
+            return Err(Exclusion::RestGap);
+        }
+
+        Ok(())
+    }
+
+    /// Score between zero and `jitter` drawn from the seed for giving a
+    /// subject the slot starting at `start`.
+    pub fn perturbation(&self, subject: Id, start: i64) -> f64 {
+        let draw = tiebreak(self.seed ^ start as u64, subject);
+
+        self.jitter * (draw as f64 / u64::MAX as f64)
+    }
+
+    /// Preference bonus for giving a subject the slot starting at `start`,
+    /// for contiguity, for steering toward their target and from the seed.
+    pub fn bonus(&self, subject: Id, start: i64, ledger: &Ledger) -> f64 {
+        let adjacent = ledger
+            .starts(subject)
+            .iter()
+            .any(|&s| (start - s).abs() == SLOT_LENGTH);
+
+        let contiguity = if adjacent { self.contiguous_bonus } else { 0.0 };
+
+        contiguity + self.steer(subject, ledger.count(subject)) + self.perturbation(subject, start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use souvenir::id;
+
+    use super::*;
+    use crate::Subject;
+
+    const HOUR: i64 = SLOT_LENGTH;
+
+    fn ledger(subject: Id, hours: &[i64]) -> Ledger {
+        let mut ledger = Ledger::default();
+
+        for hour in hours {
+            ledger.add(subject, hour * HOUR);
+        }
+
+        ledger
+    }
+
+    #[test]
+    fn shift_caps_can_be_overridden_per_subject() {
+        let (capped, trusted) = (id!(Subject), id!(Subject));
+        let constraints = Constraints {
+            max_shifts: Some(1),
+            subject_max_shifts: HashMap::from([(trusted, 2)]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            constraints.admits(capped, 5 * HOUR, &ledger(capped, &[0])),
+            Err(Exclusion::ShiftCap)
+        );
+        assert_eq!(
+            constraints.admits(trusted, 5 * HOUR, &ledger(trusted, &[0])),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn day_caps_follow_the_utc_offset() {
+        let subject = id!(Subject);
+        let held = ledger(subject, &[19]);
+        let constraints = Constraints {
+            max_shifts_per_day: Some(1),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            constraints.admits(subject, 22 * HOUR, &held),
+            Err(Exclusion::DayCap)
+        );
+        assert_eq!(constraints.admits(subject, 26 * HOUR, &held), Ok(()));
+
+        // Four hours ahead of UTC, 19:00 and 22:00 UTC fall on different days.
+        let ahead = Constraints {
+            utc_offset: 4 * HOUR,
+            ..constraints
+        };
+
+        assert_eq!(ahead.admits(subject, 22 * HOUR, &held), Ok(()));
+    }
+
+    #[test]
+    fn rest_is_measured_from_the_end_of_a_shift() {
+        let subject = id!(Subject);
+        let held = ledger(subject, &[10]);
+        let constraints = Constraints {
+            min_rest: 2 * HOUR,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            constraints.admits(subject, 12 * HOUR, &held),
+            Err(Exclusion::RestGap)
+        );
+        assert_eq!(
+            constraints.admits(subject, 8 * HOUR, &held),
+            Err(Exclusion::RestGap)
+        );
+        assert_eq!(constraints.admits(subject, 13 * HOUR, &held), Ok(()));
+        assert_eq!(constraints.admits(subject, 7 * HOUR, &held), Ok(()));
+        assert_eq!(
+            constraints.admits(subject, 10 * HOUR, &held),
+            Err(Exclusion::RestGap)
+        );
+    }
+
+    #[test]
+    fn adjacent_shifts_form_a_block_with_a_contiguity_bonus() {
+        let subject = id!(Subject);
+        let held = ledger(subject, &[10, 11]);
+        let constraints = Constraints {
+            min_rest: 2 * HOUR,
+            contiguous_bonus: 1.0,
+            ..Default::default()
+        };
+
+        assert_eq!(constraints.admits(subject, 12 * HOUR, &held), Ok(()));
+        assert_eq!(constraints.admits(subject, 9 * HOUR, &held), Ok(()));
+        assert_eq!(
+            constraints.admits(subject, 13 * HOUR, &held),
+            Err(Exclusion::RestGap)
+        );
+        assert_eq!(constraints.admits(subject, 14 * HOUR, &held), Ok(()));
+        assert_eq!(constraints.bonus(subject, 12 * HOUR, &held), 1.0);
+        assert_eq!(constraints.bonus(subject, 14 * HOUR, &held), 0.0);
+    }
+
+    #[test]
+    fn splitting_a_block_can_leave_too_little_rest() {
+        let subject = id!(Subject);
+        let mut held = ledger(subject, &[10, 11, 12]);
+        let constraints = Constraints {
+            min_rest: 2 * HOUR,
+            contiguous_bonus: 1.0,
+            ..Default::default()
+        };
+
+        assert!(constraints.rested(subject, &held));
+
+        held.remove(subject, 11 * HOUR);
+
+        assert!(!constraints.rested(subject, &held));
+    }
+}
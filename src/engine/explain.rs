@@ -0,0 +1,122 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+use souvenir::Id;
+
+use crate::{weights, Constraints, Demand, Exclusion, Factors, Ledger, ScoringStrategy};
+
+/// One candidate weighed for a slot.
+#[derive(Debug, Clone, Serialize)]
+pub struct Consideration {
+    pub slot: Id,
+    pub subject: Id,
+    pub factors: Factors,
+    pub weight: f64,
+    pub chosen: bool,
+    /// Set when a constraint kept the subject out of the slot.
+    pub excluded: Option<Exclusion>,
+}
+
+/// Breaks down how every candidate scored for every slot, whichever solver
+/// chose the assignments. Each slot is explained where its first assignment
+/// falls in `assignments`, against the shifts assigned before that point, so
+/// candidates are weighed the same way [`weights`] weighs assignments. A
+/// chosen candidate's factors and weight are exactly those of its own entry
+/// in [`weights`]. Slots nobody was assigned to are explained last, against
+/// the whole schedule.
+pub fn explain(
+    assignments: &[(Id, Id)],
+    demands: &[Demand],
+    baseline: &HashMap<Id, Factors>,
+    constraints: &Constraints,
+    scoring: &impl ScoringStrategy,
+) -> Vec<Consideration> {
+    let starts: HashMap<Id, i64> = demands.iter().map(|d| (d.slot, d.start)).collect();
+    let weights = weights(assignments, &starts, baseline, constraints, scoring);
+
+    // Shifts each chosen subject held just before their assignment, and the
+    // weight it was given.
+    let mut chosen: HashMap<(Id, Id), (u32, f64)> = HashMap::new();
+    let mut ledger = Ledger::default();
+
+    for (&(slot, subject), &weight) in assignments.iter().zip(&weights) {
+        chosen.insert((slot, subject), (ledger.count(subject), weight));
+        ledger.add(subject, starts[&slot]);
+    }
+
+    let mut seen: HashSet<Id> = HashSet::new();
+    let mut order: Vec<(Id, usize)> = Vec::new();
+
+    for (position, &(slot, _)) in assignments.iter().enumerate() {
+        if seen.insert(slot) {
+            order.push((slot, position));
+        }
+    }
+
+    for demand in demands {
+        if seen.insert(demand.slot) {
+            order.push((demand.slot, assignments.len()));
+        }
+    }
+
+    let by_slot: HashMap<Id, &Demand> = demands.iter().map(|d| (d.slot, d)).collect();
+    let mut ledger = Ledger::default();
+    let mut replayed = 0;
+    let mut considerations = Vec::new();
+
+    for (slot, position) in order {
+        for &(slot, subject) in &assignments[replayed..position] {
+            ledger.add(subject, starts[&slot]);
+        }
+        replayed = replayed.max(position);
+
+        let demand = by_slot[&slot];
+        let members: Vec<Id> = assignments
+            .iter()
+            .filter(|&&(s, _)| s == slot)
+            .map(|&(_, subject)| subject)
+            .collect();
+
+        let subjects = demand
+            .candidates
+            .iter()
+            .chain(members.iter().filter(|m| !demand.candidates.contains(m)));
+
+        for &subject in subjects {
+            let Some(factors) = baseline.get(&subject) else {
+                continue;
+            };
+
+            let consideration = match chosen.get(&(slot, subject)) {
+                Some(&(held, weight)) => Consideration {
+                    slot,
+                    subject,
+                    factors: factors.with_shifts(held),
+                    weight,
+                    chosen: true,
+                    excluded: None,
+                },
+                None => {
+                    let factors = factors.with_shifts(ledger.count(subject));
+
+                    Consideration {
+                        slot,
+                        subject,
+                        factors,
+                        weight: scoring.score(&factors)
+                            + constraints.bonus(subject, demand.start, &ledger),
+                        chosen: false,
+                        excluded: constraints
+                            .admits(subject, demand.start, &ledger)
+                            .and_then(|()| constraints.pairs_with(subject, &members))
+                            .err(),
+                    }
+                }
+            };
+
+            considerations.push(consideration);
+        }
+    }
+
+    considerations
+}
@@ -0,0 +1,174 @@
+use std::collections::VecDeque;
+
+/// A flow network solved for minimum-cost maximum flow using successive
+/// shortest paths. Costs may be negative as long as the network starts
+/// without negative cycles.
+#[derive(Default)]
+pub struct Network {
+    edges: Vec<Edge>,
+    adjacency: Vec<Vec<usize>>,
+}
+
+struct Edge {
+    to: usize,
+    capacity: i64,
+    cost: i64,
+}
+
+impl Network {
+    pub fn node(&mut self) -> usize {
+        self.adjacency.push(Vec::new());
+        self.adjacency.len() - 1
+    }
+
+    /// Adds an edge and returns a handle that can later be passed to
+    /// [`Network::flow`].
+    pub fn edge(&mut self, from: usize, to: usize, capacity: i64, cost: i64) -> usize {
+        let index = self.edges.len();
+
+        self.edges.push(Edge { to, capacity, cost });
+        self.edges.push(Edge {
+            to: from,
+            capacity: 0,
+            cost: -cost,
+        });
+
+        self.adjacency[from].push(index);
+        self.adjacency[to].push(index + 1);
+
+        index
+    }
+
+    /// Flow currently routed through an edge.
+    pub fn flow(&self, edge: usize) -> i64 {
+        self.edges[edge ^ 1].capacity
+    }
+
+    /// Pushes as much flow as possible from `source` to `sink` at the lowest
+    /// total cost, returning the flow and its cost.
+    pub fn solve(&mut self, source: usize, sink: usize) -> (i64, i64) {
+        let nodes = self.adjacency.len();
+        let (mut flow, mut cost) = (0, 0);
+
+        loop {
+            let mut distance = vec![i64::MAX; nodes];
+            let mut via = vec![usize::MAX; nodes];
+            let mut queued = vec![false; nodes];
+            let mut queue = VecDeque::from([source]);
+
+            distance[source] = 0;
+
+            while let Some(node) = queue.pop_front() {
+                queued[node] = false;
+
+                for &index in &self.adjacency[node] {
+                    let edge = &self.edges[index];
+
+                    // Costs too large to add up are treated as unreachable
+                    // rather than wrapping around.
+                    let Some(candidate) = distance[node].checked_add(edge.cost) else {
+                        continue;
+                    };
+
+                    if edge.capacity > 0 && candidate < distance[edge.to] {
+                        distance[edge.to] = candidate;
+                        via[edge.to] = index;
+
+                        if !queued[edge.to] {
+                            queued[edge.to] = true;
+                            queue.push_back(edge.to);
+                        }
+                    }
+                }
+            }
+
+            if distance[sink] == i64::MAX {
+                return (flow, cost);
+            }
+
+            let mut amount = i64::MAX;
+            let mut node = sink;
+
+            while node != source {
+                amount = amount.min(self.edges[via[node]].capacity);
+                node = self.edges[via[node] ^ 1].to;
+            }
+
+            node = sink;
+
+            while node != source {
+                self.edges[via[node]].capacity -= amount;
+                self.edges[via[node] ^ 1].capacity += amount;
+                node = self.edges[via[node] ^ 1].to;
+            }
+
+            flow += amount;
+            cost = cost.saturating_add(amount.saturating_mul(distance[sink]));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn routes_flow_along_the_cheapest_edges() {
+        let mut network = Network::default();
+        let [source, middle, sink] = [(); 3].map(|_| network.node());
+
+        network.edge(source, middle, 2, 0);
+        let dear = network.edge(middle, sink, 1, 3);
+        let cheap = network.edge(middle, sink, 1, 1);
+        let unused = network.edge(middle, sink, 1, 9);
+
+        assert_eq!(network.solve(source, sink), (2, 4));
+        assert_eq!(
+            [dear, cheap, unused].map(|edge| network.flow(edge)),
+            [1, 1, 0]
+        );
+    }
+
+    #[test]
+    fn prefers_negative_costs() {
+        let mut network = Network::default();
+        let [source, left, right, sink] = [(); 4].map(|_| network.node());
+
+        network.edge(source, left, 1, 0);
+        network.edge(source, right, 1, 0);
+        let reward = network.edge(left, sink, 1, -5);
+        network.edge(right, sink, 1, 2);
+
+        assert_eq!(network.solve(source, sink), (2, -3));
+        assert_eq!(network.flow(reward), 1);
+    }
+
+    #[test]
+    fn reroutes_earlier_flow_to_push_more() {
+        // The cheapest path runs through the middle edge, and the second
+        // path has to undo it to reach the sink at all.
+        let mut network = Network::default();
+        let [source, a, b, sink] = [(); 4].map(|_| network.node());
+
+        network.edge(source, a, 1, 0);
+        let middle = network.edge(a, b, 1, 0);
+        network.edge(b, sink, 1, 0);
+        network.edge(source, b, 1, 2);
+        network.edge(a, sink, 1, 2);
+
+        assert_eq!(network.solve(source, sink), (2, 4));
+        assert_eq!(network.flow(middle), 0);
+    }
+
+    #[test]
+    fn treats_costs_too_large_to_add_as_unreachable() {
+        let mut network = Network::default();
+        let [source, a, b, sink] = [(); 4].map(|_| network.node());
+
+        network.edge(source, a, 1, i64::MAX / 2);
+        network.edge(a, b, 1, i64::MAX / 2);
+        network.edge(b, sink, 1, i64::MAX / 2);
+
+        assert_eq!(network.solve(source, sink), (0, 0));
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use souvenir::Id;
+
+use crate::Factors;
+
+/// How much older shifts count towards a subject's history.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum Decay {
+    /// Every shift counts fully, however old.
+    #[default]
+    None,
+    /// A shift counts half as much every `half_life` schedules back.
+    Exponential { half_life: f64 },
+    /// Only shifts in the last `schedules` schedules count, and subjects not
+    /// scheduled within them are treated as never scheduled.
+    Window { schedules: u64 },
+}
+
+impl Decay {
+    /// Weight of a shift `depth` schedules up the ancestry.
+    pub fn weight(&self, depth: u64) -> f64 {
+        match *self {
+            Decay::None => 1.0,
+            Decay::Exponential { half_life } => 0.5f64.powf(depth as f64 / half_life),
+            Decay::Window { schedules } if depth <= schedules => 1.0,
+            Decay::Window { .. } => 0.0,
+        }
+    }
+
+    /// Rejects a half-life that would make weights undefined and a window
+    /// that would forget every earlier schedule.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        match *self {
+            Decay::None => {}
+            Decay::Exponential { half_life } => anyhow::ensure!(
+                half_life.is_finite() && half_life > 0.0,
+                "half_life must be a positive number"
+            ),
+            Decay::Window { schedules } => {
+                anyhow::ensure!(schedules > 0, "schedules must be at least 1")
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Per-subject shift history along a schedule's ancestry.
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    subjects: HashMap<Id, Vec<Entry>>,
+    decay: Decay,
+    /// Weight of shifts from schedules outside the term of the schedule the
+    /// history was loaded for. `None` counts them in full.
+    carry_over: Option<f64>,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Entry {
+    depth: u64,
+    count: u32,
+    outside_term: bool,
+}
+
+impl History {
+    /// Applies `decay` to the shifts counted by [`Self::factors`].
+    pub fn decay(self, decay: Decay) -> Self {
+        Self { decay, ..self }
+    }
+
+    /// Weighs shifts from outside the term by `carry_over` in
+    /// [`Self::factors`].
+    pub fn carry_over(self, carry_over: f64) -> Self {
+        Self {
+            carry_over: Some(carry_over),
+            ..self
+        }
+    }
+
+    /// Records `count` shifts for a subject in the schedule `depth` steps up
+    /// the ancestry, where the schedule itself is at depth zero.
+    pub fn insert(&mut self, subject: Id, depth: u64, count: u32, outside_term: bool) {
+        self.subjects.entry(subject).or_default().push(Entry {
+            depth,
+            count,
+            outside_term,
+        });
+    }
+
+    fn weight(&self, entry: &Entry) -> f64 {
+        let carried = match entry.outside_term {
+            true => self.carry_over.unwrap_or(1.0),
+            false => 1.0,
+        };
+
+        self.decay.weight(entry.depth) * carried
+    }
+
+    /// Shifts held across the whole ancestry.
+    pub fn total(&self, subject: Id) -> u32 {
+        self.subjects
+            .get(&subject)
+            .map_or(0, |entries| entries.iter().map(|e| e.count).sum())
+    }
+
+    /// Shifts held across the ancestry, weighted down by the decay and
+    /// carry-over.
+    pub fn weighted_total(&self, subject: Id) -> f64 {
+        self.subjects.get(&subject).map_or(0.0, |entries| {
+            entries
+                .iter()
+                .map(|e| self.weight(e) * e.count as f64)
+                .sum()
+        })
+    }
+
+    /// Depth of the closest schedule the subject held a shift in.
+    pub fn last_scheduled(&self, subject: Id) -> Option<u64> {
+        self.subjects.get(&subject)?.iter().map(|e| e.depth).min()
+    }
+
+    /// Scoring factors for a subject that holds no shift yet in the schedule
+    /// this history was loaded for. Shifts weighted down to nothing are
+    /// left out of `weeks_since` too.
+    pub fn factors(&self, subject: Id, flexibility: u32) -> Factors {
+        let weeks_since = self.subjects.get(&subject).and_then(|entries| {
+            entries
+                .iter()
+                .filter(|e| self.weight(e) > 0.0)
+                .map(|e| e.depth)
+                .min()
+        });
+
+        Factors {
+            weeks_since,
+            flexibility,
+            total_shifted: self.weighted_total(subject),
+            shifts_current: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use souvenir::id;
+
+    use super::*;
+    use crate::Subject;
+
+    #[test]
+    fn decay_weighs_older_shifts_less() {
+        assert_eq!(Decay::None.weight(10), 1.0);
+        assert_eq!(Decay::Exponential { half_life: 2.0 }.weight(0), 1.0);
+        assert_eq!(Decay::Exponential { half_life: 2.0 }.weight(2), 0.5);
+        assert_eq!(Decay::Exponential { half_life: 2.0 }.weight(4), 0.25);
+        assert_eq!(Decay::Window { schedules: 2 }.weight(2), 1.0);
+        assert_eq!(Decay::Window { schedules: 2 }.weight(3), 0.0);
+    }
+
+    #[test]
+    fn validate_rejects_decay_that_never_applies_or_forgets_everything() {
+        assert!(Decay::None.validate().is_ok());
+        assert!(Decay::Exponential { half_life: 2.0 }.validate().is_ok());
+        assert!(Decay::Exponential { half_life: 0.0 }.validate().is_err());
+        assert!(Decay::Exponential { half_life: -1.0 }.validate().is_err());
+        assert!(Decay::Exponential {
+            half_life: f64::NAN
+        }
+        .validate()
+        .is_err());
+        assert!(Decay::Window { schedules: 1 }.validate().is_ok());
+        assert!(Decay::Window { schedules: 0 }.validate().is_err());
+    }
+
+    #[test]
+    fn shifts_outside_the_window_are_forgotten() {
+        let subject = id!(Subject);
+        let mut history = History::default();
+        history.insert(subject, 3, 1, false);
+
+        let history = history.decay(Decay::Window { schedules: 2 });
+        let factors = history.factors(subject, 5);
+
+        assert_eq!(history.last_scheduled(subject), Some(3));
+        assert_eq!(factors.weeks_since, None);
+        assert_eq!(factors.total_shifted, 0.0);
+        assert_eq!(factors.flexibility, 5);
+    }
+
+    #[test]
+    fn carry_over_weighs_shifts_from_outside_the_term() {
+        let subject = id!(Subject);
+        let mut history = History::default();
+        history.insert(subject, 1, 2, false);
+        history.insert(subject, 2, 4, true);
+
+        assert_eq!(history.weighted_total(subject), 6.0);
+
+        let history = history.carry_over(0.25);
+
+        assert_eq!(history.total(subject), 6);
+        assert_eq!(history.weighted_total(subject), 3.0);
+        assert_eq!(history.factors(subject, 5).total_shifted, 3.0);
+    }
+}
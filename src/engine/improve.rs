@@ -0,0 +1,205 @@
+use std::collections::{HashMap, HashSet};
+
+use souvenir::Id;
+
+use crate::{Constraints, Demand, Factors, Ledger, RoleRequirement, ScoringStrategy};
+
+/// Improvements smaller than this are treated as rounding noise.
+const EPSILON: f64 = 1e-9;
+
+/// Score of each assignment, in order. Assignments are replayed the way the
+/// greedy solver places them: a subject's `n`th assignment is scored as if
+/// they held `n - 1` shifts already, steered toward their target, and earns
+/// the contiguity bonus when an earlier assignment of theirs is adjacent.
+pub fn weights(
+    assignments: &[(Id, Id)],
+    starts: &HashMap<Id, i64>,
+    baseline: &HashMap<Id, Factors>,
+    constraints: &Constraints,
+    scoring: &impl ScoringStrategy,
+) -> Vec<f64> {
+    let mut ledger = Ledger::default();
+
+    assignments
+        .iter()
+        .map(|&(slot, subject)| {
+            let start = starts[&slot];
+            let weight = baseline.get(&subject).map_or(0.0, |factors| {
+                scoring.score(&factors.with_shifts(ledger.count(subject)))
+                    + constraints.bonus(subject, start, &ledger)
+            });
+
+            ledger.add(subject, start);
+            weight
+        })
+        .collect()
+}
+
+/// Summed score of a set of assignments, as given by [`weights`].
+pub fn objective(
+    assignments: &[(Id, Id)],
+    starts: &HashMap<Id, i64>,
+    baseline: &HashMap<Id, Factors>,
+    constraints: &Constraints,
+    scoring: &impl ScoringStrategy,
+) -> f64 {
+    weights(assignments, starts, baseline, constraints, scoring)
+        .into_iter()
+        .sum()
+}
+
+/// Local search over finished assignments. Repeatedly applies the first move
+/// that improves the schedule until none does, and returns how many moves
+/// were applied. Two kinds of move are tried:
+///
+/// - handing a seat to an available subject not already on that slot, which
+///   evens out shift counts, and
+/// - swapping two subjects between slots they are both available for, which
+///   keeps shift counts but changes who works next to whom and when.
+///
+/// A move improves the schedule if it leaves fewer pairing rules broken and
+/// role requirements unmet, or as many and a higher [`objective`], which
+/// rewards contiguous blocks. The first `fixed` assignments are never moved.
+/// Neither move changes how many subjects a slot holds, and neither may
+/// break `constraints`, including by splitting a block too close together.
+pub fn improve(
+    assignments: &mut [(Id, Id)],
+    fixed: usize,
+    demands: &[Demand],
+    baseline: &HashMap<Id, Factors>,
+    constraints: &Constraints,
+    scoring: &impl ScoringStrategy,
+) -> u32 {
+    let candidates: HashMap<Id, &[Id]> = demands
+        .iter()
+        .map(|demand| (demand.slot, demand.candidates.as_slice()))
+        .collect();
+
+    let available = |slot: Id, subject: Id| {
+        candidates
+            .get(&slot)
+            .is_some_and(|candidates| candidates.contains(&subject))
+    };
+
+    let requirements: HashMap<Id, &[RoleRequirement]> = demands
+        .iter()
+        .map(|demand| (demand.slot, demand.requirements.as_slice()))
+        .collect();
+
+    let broken = |assignments: &[(Id, Id)], slot: Id| {
+        let members: Vec<Id> = assignments
+            .iter()
+            .filter(|&&(s, _)| s == slot)
+            .map(|&(_, subject)| subject)
+            .collect();
+
+        constraints.broken(&members).len() as u32
+            + constraints.shortfall(requirements[&slot], &members)
+    };
+
+    let starts: HashMap<Id, i64> = demands.iter().map(|d| (d.slot, d.start)).collect();
+    let mut taken: HashSet<(Id, Id)> = assignments.iter().copied().collect();
+    let mut ledger = Ledger::default();
+
+    for (slot, subject) in assignments.iter() {
+        ledger.add(*subject, starts[slot]);
+    }
+
+    let mut current = objective(assignments, &starts, baseline, constraints, scoring);
+    let mut improvements = 0;
+
+    let better = |score: f64, current: f64, after: u32, before: u32| {
+        after < before || (after == before && score > current + EPSILON)
+    };
+
+    'search: loop {
+        for i in fixed..assignments.len() {
+            let (slot, subject) = assignments[i];
+
+            let start = starts[&slot];
+            let before = broken(assignments, slot);
+
+            ledger.remove(subject, start);
+            let releasable = constraints.rested(subject, &ledger);
+            ledger.add(subject, start);
+
+            for &other in candidates.get(&slot).copied().unwrap_or_default() {
+                if !releasable
+                    || taken.contains(&(slot, other))
+                    || !baseline.contains_key(&other)
+                    || constraints.admits(other, start, &ledger).is_err()
+                {
+                    continue;
+                }
+
+                assignments[i].1 = other;
+                let score = objective(assignments, &starts, baseline, constraints, scoring);
+
+                if better(score, current, broken(assignments, slot), before) {
+                    taken.remove(&(slot, subject));
+                    taken.insert((slot, other));
+                    ledger.remove(subject, start);
+                    ledger.add(other, start);
+                    current = score;
+                    improvements += 1;
+                    continue 'search;
+                }
+
+                assignments[i].1 = subject;
+            }
+
+            for j in i + 1..assignments.len() {
+                let (other_slot, other) = assignments[j];
+
+                if slot == other_slot
+                    || taken.contains(&(slot, other))
+                    || taken.contains(&(other_slot, subject))
+                    || !available(slot, other)
+                    || !available(other_slot, subject)
+                {
+                    continue;
+                }
+
+                let other_start = starts[&other_slot];
+                ledger.remove(subject, start);
+                ledger.remove(other, other_start);
+
+                if constraints.admits(subject, other_start, &ledger).is_ok()
+                    && constraints.admits(other, start, &ledger).is_ok()
+                {
+                    ledger.add(subject, other_start);
+                    ledger.add(other, start);
+
+                    if constraints.rested(subject, &ledger) && constraints.rested(other, &ledger) {
+                        let before = before + broken(assignments, other_slot);
+                        assignments[i].1 = other;
+                        assignments[j].1 = subject;
+                        let score = objective(assignments, &starts, baseline, constraints, scoring);
+                        let after = broken(assignments, slot) + broken(assignments, other_slot);
+
+                        if better(score, current, after, before) {
+                            taken.remove(&(slot, subject));
+                            taken.remove(&(other_slot, other));
+                            taken.insert((slot, other));
+                            taken.insert((other_slot, subject));
+                            current = score;
+                            improvements += 1;
+                            continue 'search;
+                        }
+
+                        assignments[i].1 = subject;
+                        assignments[j].1 = other;
+                    }
+
+                    ledger.remove(subject, other_start);
+                    ledger.remove(other, start);
+                }
+
+                ledger.add(subject, start);
+                ledger.add(other, other_start);
+            }
+        }
+
+        return improvements;
+    }
+}
@@ -0,0 +1,26 @@
+mod constraints;
+mod explain;
+mod flow;
+mod history;
+mod improve;
+mod pairing;
+mod problem;
+mod roles;
+mod scoring;
+mod solver;
+mod stats;
+mod targets;
+mod validate;
+
+pub use constraints::*;
+pub use explain::*;
+pub use history::*;
+pub use improve::*;
+pub use pairing::*;
+pub use problem::*;
+pub use roles::*;
+pub use scoring::*;
+pub use solver::*;
+pub use stats::*;
+pub use targets::*;
+pub use validate::*;
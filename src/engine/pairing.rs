@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use souvenir::Id;
+
+use crate::{Constraints, Exclusion};
+
+/// How a pairing rule relates its two subjects.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PairingKind {
+    /// The subject may only work a slot alongside the partner. A subject
+    /// with several such rules needs any one of their partners.
+    MustPair,
+    /// The subject and the partner never share a slot.
+    NeverPair,
+}
+
+impl PairingKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PairingKind::MustPair => "must_pair",
+            PairingKind::NeverPair => "never_pair",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "must_pair" => Some(PairingKind::MustPair),
+            "never_pair" => Some(PairingKind::NeverPair),
+            _ => None,
+        }
+    }
+}
+
+/// A rule on which subjects may share a slot.
+#[derive(Debug, Copy, Clone, Serialize)]
+pub struct Pairing {
+    pub rule: Id,
+    pub kind: PairingKind,
+    pub subject: Id,
+    pub partner: Id,
+}
+
+/// A pairing rule broken by the generated schedule, and where.
+#[derive(Debug, Clone, Serialize)]
+pub struct Unsatisfied {
+    #[serde(flatten)]
+    pub pairing: Pairing,
+    pub slots: Vec<Id>,
+}
+
+impl Constraints {
+    /// Subjects `subject` must share a slot with, one of which is enough.
+    pub fn partners(&self, subject: Id) -> impl Iterator<Item = Id> + '_ {
+        self.pairings
+            .iter()
+            .filter(move |p| p.kind == PairingKind::MustPair && p.subject == subject)
+            .map(|p| p.partner)
+    }
+
+    /// Checks whether a subject may join a slot already holding `seated`.
+    pub fn pairs_with(&self, subject: Id, seated: &[Id]) -> Result<(), Exclusion> {
+        let never = self.pairings.iter().any(|p| {
+            p.kind == PairingKind::NeverPair
+                && ((p.subject == subject && seated.contains(&p.partner))
+                    || (p.partner == subject && seated.contains(&p.subject)))
+        });
+
+        if never {
+            return Err(Exclusion::NeverPair);
+        }
+
+        let mut partners = self.partners(subject).peekable();
+
+        if partners.peek().is_some() && !partners.any(|p| seated.contains(&p)) {
+            return Err(Exclusion::MustPair);
+        }
+
+        Ok(())
+    }
+
+    /// Rules broken by a slot holding `members`.
+    pub fn broken(&self, members: &[Id]) -> Vec<Pairing> {
+        self.pairings
+            .iter()
+            .filter(|p| match p.kind {
+                PairingKind::NeverPair => {
+                    members.contains(&p.subject) && members.contains(&p.partner)
+                }
+                PairingKind::MustPair => {
+                    members.contains(&p.subject)
+                        && !self.partners(p.subject).any(|q| members.contains(&q))
+                }
+            })
+            .copied()
+            .collect()
+    }
+
+    /// Rules broken anywhere in `assignments`, with the slots breaking them.
+    pub fn unsatisfied(&self, assignments: &[(Id, Id)]) -> Vec<Unsatisfied> {
+        let mut members: Vec<(Id, Vec<Id>)> = Vec::new();
+
+        for &(slot, subject) in assignments {
+            match members.iter_mut().find(|(s, _)| *s == slot) {
+                Some((_, subjects)) => subjects.push(subject),
+                None => members.push((slot, vec![subject])),
+            }
+        }
+
+        let mut unsatisfied: Vec<Unsatisfied> = Vec::new();
+        let mut index: HashMap<Id, usize> = HashMap::new();
+
+        for (slot, subjects) in members {
+            for pairing in self.broken(&subjects) {
+                let position = *index.entry(pairing.rule).or_insert_with(|| {
+                    unsatisfied.push(Unsatisfied {
+                        pairing,
+                        slots: Vec::new(),
+                    });
+
+                    unsatisfied.len() - 1
+                });
+
+                unsatisfied[position].slots.push(slot);
+            }
+        }
+
+        unsatisfied
+    }
+}
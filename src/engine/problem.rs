@@ -0,0 +1,606 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+use souvenir::Id;
+
+use crate::{
+    engine, Consideration, Constraints, Demand, Deviation, Exclusion, Factors, Fairness, History,
+    Ledger, RoleRequirement, Scoring, Solver, Staffing, Tally, Unmet, Unsatisfied,
+};
+
+/// A slot of the schedule being generated.
+#[derive(Debug, Clone)]
+pub struct SlotSpec {
+    pub id: Id,
+    pub w2m_id: i32,
+    pub staffing: Staffing,
+    pub requirements: Vec<RoleRequirement>,
+}
+
+/// Everything needed to generate a schedule, with no database access.
+#[derive(Debug, Clone, Default)]
+pub struct Problem {
+    pub slots: Vec<SlotSpec>,
+    /// Subjects available for each slot.
+    pub availability: HashMap<Id, Vec<Id>>,
+    /// History along the ancestry of the schedule being generated.
+    pub history: History,
+    pub constraints: Constraints,
+    /// `(slot, subject)` pairs agreed ahead of time. They are placed first,
+    /// whether or not the subject marked themselves available.
+    pub pins: Vec<(Id, Id)>,
+    /// Subjects who have left. They are never given a slot they are not
+    /// pinned to, whatever the availability says.
+    pub inactive: HashSet<Id>,
+    /// `(slot, subject)` pairs inside the subject's time off, with the time
+    /// off they fall in. Time off overrides the availability.
+    pub time_off: HashMap<(Id, Id), Id>,
+}
+
+/// Bumped whenever a change to the engine may change the schedule it
+/// generates for the same problem and options.
+pub const ALGORITHM_VERSION: i32 = 9;
+
+/// How a problem should be solved.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Options {
+    pub scoring: Scoring,
+    pub solver: Solver,
+    pub improve: bool,
+    /// Perturbs every score by up to the constraints' `jitter` and decides
+    /// the order candidates with equal scores are considered in. The same
+    /// problem, options and seed always give the same schedule.
+    pub seed: u64,
+}
+
+/// A slot that ended up with fewer subjects than its minimum headcount.
+#[derive(Debug, Clone, Serialize)]
+pub struct Understaffed {
+    pub slot: Id,
+    pub w2m_id: i32,
+    pub required: u32,
+    pub assigned: u32,
+    /// Available subjects a constraint kept out of the slot.
+    pub excluded: Vec<Excluded>,
+}
+
+/// An available subject left out of a slot, and why.
+#[derive(Debug, Clone, Serialize)]
+pub struct Excluded {
+    pub subject: Id,
+    pub reason: Exclusion,
+}
+
+/// The result of solving a [`Problem`].
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    /// `(slot, subject)` pairs, in the order they were decided.
+    pub assignments: Vec<(Id, Id)>,
+    /// Weight of each assignment, aligned with `assignments`.
+    pub weights: Vec<f64>,
+    pub understaffed: Vec<Understaffed>,
+    /// Role requirements the assignments fall short of.
+    pub unmet: Vec<Unmet>,
+    /// Pairing rules the assignments break.
+    pub unsatisfied: Vec<Unsatisfied>,
+    /// How far every subject with a target lands from it.
+    pub deviations: Vec<Deviation>,
+    pub improvements: u32,
+    pub considerations: Vec<Consideration>,
+    pub fairness: Fairness,
+    pub objective: f64,
+}
+
+impl Problem {
+    /// Whether a subject may work a slot they marked themselves available
+    /// for: they must be active and not on time off.
+    pub fn open(&self, slot: Id, subject: Id) -> bool {
+        !self.inactive.contains(&subject) && !self.time_off.contains_key(&(slot, subject))
+    }
+
+    /// Subjects available for a slot who may work it.
+    pub fn available(&self, slot: Id) -> impl Iterator<Item = Id> + '_ {
+        self.availability
+            .get(&slot)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(move |&subject| self.open(slot, subject))
+    }
+
+    /// Availability of active subjects overridden by time off, as
+    /// `(slot, subject, time off)` in slot order.
+    pub fn withdrawn(&self) -> Vec<(Id, Id, Id)> {
+        let mut withdrawn = Vec::new();
+
+        for slot in self.slots.iter() {
+            let mut subjects: Vec<Id> = self
+                .availability
+                .get(&slot.id)
+                .into_iter()
+                .flatten()
+                .copied()
+                .filter(|subject| !self.inactive.contains(subject))
+                .collect();
+            subjects.sort();
+
+            for subject in subjects {
+                if let Some(&time_off) = self.time_off.get(&(slot.id, subject)) {
+                    withdrawn.push((slot.id, subject, time_off));
+                }
+            }
+        }
+
+        withdrawn
+    }
+
+    /// Scoring factors for every available or pinned subject before the new
+    /// schedule gives them any shift.
+    pub fn baseline(&self) -> HashMap<Id, Factors> {
+        let mut flexibility: HashMap<Id, u32> = HashMap::new();
+
+        for slot in self.availability.keys() {
+            for subject in self.available(*slot) {
+                *flexibility.entry(subject).or_default() += 1;
+            }
+        }
+
+        for &(_, subject) in self.pins.iter() {
+            flexibility.entry(subject).or_default();
+        }
+
+        flexibility
+            .into_iter()
+            .map(|(subject, flexibility)| (subject, self.history.factors(subject, flexibility)))
+            .collect()
+    }
+
+    /// Slots with at least one available or pinned subject, least flexible
+    /// first.
+    pub fn demands(&self) -> Vec<Demand> {
+        let mut demands: Vec<(i32, Demand)> = self
+            .slots
+            .iter()
+            .filter_map(|slot| {
+                let pinned = self.pins.iter().any(|&(s, _)| s == slot.id);
+                let candidates: Vec<Id> = self.available(slot.id).collect();
+
+                if candidates.is_empty() && !pinned {
+                    return None;
+                }
+
+                Some((
+                    slot.w2m_id,
+                    Demand {
+                        slot: slot.id,
+                        start: slot.w2m_id as i64,
+                        staffing: slot.staffing,
+                        requirements: slot.requirements.clone(),
+                        candidates,
+                    },
+                ))
+            })
+            .collect();
+
+        demands.sort_by_key(|(w2m_id, demand)| (demand.candidates.len(), *w2m_id));
+        demands.into_iter().map(|(_, demand)| demand).collect()
+    }
+
+    pub fn solve(&self, options: &Options) -> Outcome {
+        let scoring = &options.scoring;
+        let constraints = &Constraints {
+            seed: options.seed,
+            ..self.constraints.clone()
+        };
+        let baseline = self.baseline();
+        let mut demands = self.demands();
+
+        for demand in demands.iter_mut() {
+            demand
+                .candidates
+                .sort_by_cached_key(|&subject| tiebreak(options.seed, subject));
+        }
+
+        let pins = &self.pins;
+
+        let mut assignments = match options.solver {
+            Solver::Greedy => engine::greedy(pins, &demands, &baseline, constraints, scoring),
+            Solver::Optimal => engine::optimal(pins, &demands, &baseline, constraints, scoring),
+        };
+
+        let improvements = if options.improve {
+            let fixed = pins.len();
+            engine::improve(
+                &mut assignments,
+                fixed,
+                &demands,
+                &baseline,
+                constraints,
+                scoring,
+            )
+        } else {
+            0
+        };
+
+        let starts: HashMap<Id, i64> = demands.iter().map(|d| (d.slot, d.start)).collect();
+        let taken: HashSet<(Id, Id)> = assignments.iter().copied().collect();
+        let mut assigned: HashMap<Id, u32> = HashMap::new();
+        let mut ledger = Ledger::default();
+
+        for &(slot, subject) in assignments.iter() {
+            *assigned.entry(slot).or_default() += 1;
+            ledger.add(subject, starts[&slot]);
+        }
+
+        let understaffed = self
+            .slots
+            .iter()
+            .filter_map(|slot| {
+                let assigned = assigned.get(&slot.id).copied().unwrap_or(0);
+
+                if assigned >= slot.staffing.min {
+                    return None;
+                }
+
+                let members: Vec<Id> = assignments
+                    .iter()
+                    .filter(|&&(s, _)| s == slot.id)
+                    .map(|&(_, subject)| subject)
+                    .collect();
+
+                let excluded = self
+                    .available(slot.id)
+                    .filter(|&subject| !taken.contains(&(slot.id, subject)))
+                    .filter_map(|subject| {
+                        let reason = constraints
+                            .admits(subject, slot.w2m_id as i64, &ledger)
+                            .and_then(|()| constraints.pairs_with(subject, &members))
+                            .err()?;
+
+                        Some(Excluded { subject, reason })
+                    })
+                    .collect();
+
+                Some(Understaffed {
+                    slot: slot.id,
+                    w2m_id: slot.w2m_id,
+                    required: slot.staffing.min,
+                    assigned,
+                    excluded,
+                })
+            })
+            .collect();
+
+        let unmet = self
+            .slots
+            .iter()
+            .flat_map(|slot| {
+                let members: Vec<Id> = assignments
+                    .iter()
+                    .filter(|&&(s, _)| s == slot.id)
+                    .map(|&(_, subject)| subject)
+                    .collect();
+
+                slot.requirements.iter().filter_map(move |requirement| {
+                    let assigned = constraints.holders(requirement.role, &members);
+
+                    (assigned < requirement.count).then_some(Unmet {
+                        slot: slot.id,
+                        w2m_id: slot.w2m_id,
+                        role: requirement.role,
+                        required: requirement.count,
+                        assigned,
+                    })
+                })
+            })
+            .collect();
+
+        let tallies: Vec<Tally> = baseline
+            .keys()
+            .map(|&subject| {
+                let current = ledger.count(subject);
+
+                Tally {
+                    current,
+                    total: self.history.total(subject) + current,
+                }
+            })
+            .collect();
+
+        Outcome {
+            weights: engine::weights(&assignments, &starts, &baseline, constraints, scoring),
+            considerations: engine::explain(
+                &assignments,
+                &demands,
+                &baseline,
+                constraints,
+                scoring,
+            ),
+            objective: engine::objective(&assignments, &starts, &baseline, constraints, scoring),
+            fairness: Fairness::measure(&tallies),
+            unsatisfied: constraints.unsatisfied(&assignments),
+            deviations: constraints.deviations(|subject| ledger.count(subject)),
+            assignments,
+            understaffed,
+            unmet,
+            improvements,
+        }
+    }
+}
+
+/// Position of a subject among candidates with equal scores, shuffled by
+/// `seed`.
+pub(super) fn tiebreak(seed: u64, subject: Id) -> u64 {
+    let bytes = u128::from_le_bytes(*subject.as_bytes());
+    let mut x = seed ^ (bytes as u64) ^ ((bytes >> 64) as u64);
+
+    // splitmix64 finalizer
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use souvenir::id;
+
+    use super::*;
+    use crate::{Slot, Subject, TimeOff};
+
+    const HOUR: i32 = 60 * 60;
+
+    fn slot(hour: i32, min: u32, max: u32) -> SlotSpec {
+        SlotSpec {
+            id: id!(Slot),
+            w2m_id: hour * HOUR,
+            staffing: Staffing { min, max },
+            requirements: Vec::new(),
+        }
+    }
+
+    fn subjects(count: usize) -> Vec<Id> {
+        (0..count).map(|_| id!(Subject)).collect()
+    }
+
+    fn problem(slots: &[(&SlotSpec, &[Id])]) -> Problem {
+        Problem {
+            slots: slots.iter().map(|&(slot, _)| slot.clone()).collect(),
+            availability: slots
+                .iter()
+                .map(|&(slot, subjects)| (slot.id, subjects.to_vec()))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn solve(problem: &Problem, solver: Solver) -> Outcome {
+        problem.solve(&Options {
+            solver,
+            ..Default::default()
+        })
+    }
+
+    fn slots_of(outcome: &Outcome, subject: Id) -> HashSet<Id> {
+        outcome
+            .assignments
+            .iter()
+            .filter(|&&(_, s)| s == subject)
+            .map(|&(slot, _)| slot)
+            .collect()
+    }
+
+    #[test]
+    fn fills_slots_up_to_their_maximum() {
+        let people = subjects(3);
+        let (first, second) = (slot(0, 1, 2), slot(2, 1, 2));
+        let problem = problem(&[(&first, &people), (&second, &people)]);
+
+        for solver in [Solver::Greedy, Solver::Optimal] {
+            let outcome = solve(&problem, solver);
+
+            assert_eq!(outcome.assignments.len(), 4);
+            assert_eq!(outcome.weights.len(), 4);
+            assert!(outcome.understaffed.is_empty());
+            assert!(people.iter().all(|&p| !slots_of(&outcome, p).is_empty()));
+        }
+    }
+
+    #[test]
+    fn optimal_fills_minimum_seats_before_optional_ones() {
+        let people = subjects(2);
+        let (wide, narrow) = (slot(0, 1, 2), slot(2, 1, 1));
+        let mut problem = problem(&[(&wide, &people), (&narrow, &people)]);
+        problem.constraints.max_shifts = Some(1);
+
+        // Greedy fills the earlier slot to its maximum and strands the other.
+        let greedy = solve(&problem, Solver::Greedy);
+        assert_eq!(greedy.understaffed.len(), 1);
+        assert_eq!(greedy.understaffed[0].slot, narrow.id);
+        assert_eq!(
+            greedy.understaffed[0].excluded[0].reason,
+            Exclusion::ShiftCap
+        );
+
+        let optimal = solve(&problem, Solver::Optimal);
+        assert!(optimal.understaffed.is_empty());
+        assert_eq!(optimal.assignments.len(), 2);
+    }
+
+    #[test]
+    fn improving_never_lowers_the_objective() {
+        let people = subjects(4);
+        let slots: Vec<SlotSpec> = (0..4).map(|hour| slot(hour, 1, 2)).collect();
+        let mut problem = problem(&[
+            (&slots[0], &people[..2]),
+            (&slots[1], &people),
+            (&slots[2], &people[1..]),
+            (&slots[3], &people[2..]),
+        ]);
+        problem.constraints.contiguous_bonus = 5.0;
+
+        for solver in [Solver::Greedy, Solver::Optimal] {
+            let plain = problem.solve(&Options {
+                solver,
+                ..Default::default()
+            });
+            let improved = problem.solve(&Options {
+                solver,
+                improve: true,
+                ..Default::default()
+            });
+
+            assert!(improved.objective >= plain.objective - 1e-9);
+            assert_eq!(improved.assignments.len(), plain.assignments.len());
+        }
+    }
+
+    #[test]
+    fn rest_gap_is_measured_between_blocks() {
+        let person = subjects(1);
+        let slots = [slot(0, 1, 1), slot(1, 1, 1), slot(3, 1, 1)];
+        let mut problem = problem(&[
+            (&slots[0], &person),
+            (&slots[1], &person),
+            (&slots[2], &person),
+        ]);
+        problem.constraints.min_rest = 2 * HOUR as i64;
+
+        let apart = solve(&problem, Solver::Greedy);
+        assert_eq!(
+            slots_of(&apart, person[0]),
+            HashSet::from([slots[0].id, slots[2].id])
+        );
+
+        problem.constraints.contiguous_bonus = 1.0;
+
+        for solver in [Solver::Greedy, Solver::Optimal] {
+            let outcome = solve(&problem, solver);
+            let mut ledger = Ledger::default();
+
+            for &(slot, subject) in outcome.assignments.iter() {
+                let start = problem.slots.iter().find(|s| s.id == slot).unwrap().w2m_id;
+                ledger.add(subject, start as i64);
+            }
+
+            assert_eq!(outcome.assignments.len(), 2);
+            assert!(problem.constraints.rested(person[0], &ledger));
+        }
+
+        let block = solve(&problem, Solver::Greedy);
+        assert_eq!(
+            slots_of(&block, person[0]),
+            HashSet::from([slots[0].id, slots[1].id])
+        );
+    }
+
+    #[test]
+    fn pins_are_placed_without_availability() {
+        let people = subjects(2);
+        let only = slot(0, 1, 1);
+        let mut problem = problem(&[(&only, &people[..1])]);
+        problem.pins = vec![(only.id, people[1])];
+
+        for solver in [Solver::Greedy, Solver::Optimal] {
+            let outcome = solve(&problem, solver);
+
+            assert_eq!(outcome.assignments, vec![(only.id, people[1])]);
+        }
+    }
+
+    #[test]
+    fn same_seed_gives_same_schedule() {
+        let people = subjects(6);
+        let slots: Vec<SlotSpec> = (0..4).map(|hour| slot(hour * 3, 1, 2)).collect();
+        let problem = problem(&[
+            (&slots[0], &people),
+            (&slots[1], &people),
+            (&slots[2], &people),
+            (&slots[3], &people),
+        ]);
+
+        for solver in [Solver::Greedy, Solver::Optimal] {
+            let options = Options {
+                solver,
+                seed: 7,
+                ..Default::default()
+            };
+
+            assert_eq!(
+                problem.solve(&options).assignments,
+                problem.solve(&options).assignments
+            );
+        }
+    }
+
+    #[test]
+    fn inactive_subjects_are_never_scheduled() {
+        let people = subjects(2);
+        let only = slot(0, 1, 2);
+        let mut problem = problem(&[(&only, &people)]);
+        problem.inactive.insert(people[0]);
+
+        for solver in [Solver::Greedy, Solver::Optimal] {
+            let outcome = solve(&problem, solver);
+
+            assert_eq!(outcome.assignments, vec![(only.id, people[1])]);
+        }
+    }
+
+    #[test]
+    fn time_off_overrides_availability() {
+        let people = subjects(2);
+        let only = slot(0, 1, 2);
+        let off = id!(TimeOff);
+        let mut problem = problem(&[(&only, &people)]);
+        problem.time_off.insert((only.id, people[0]), off);
+
+        for solver in [Solver::Greedy, Solver::Optimal] {
+            let outcome = solve(&problem, solver);
+
+            assert_eq!(outcome.assignments, vec![(only.id, people[1])]);
+        }
+
+        assert_eq!(problem.withdrawn(), vec![(only.id, people[0], off)]);
+
+        // Availability withdrawn from someone who has left is not reported.
+        problem.inactive.insert(people[0]);
+        assert!(problem.withdrawn().is_empty());
+    }
+
+    #[test]
+    fn different_seeds_settle_near_ties_differently() {
+        let people = subjects(3);
+        let only = slot(0, 1, 1);
+        let spare: Vec<SlotSpec> = (1..3).map(|hour| slot(hour, 0, 0)).collect();
+
+        // Each extra slot someone could have worked makes them a little
+        // less preferred, which no seed overrides without jitter.
+        let mut problem = problem(&[
+            (&only, &people),
+            (&spare[0], &people[1..]),
+            (&spare[1], &people[2..]),
+        ]);
+
+        let schedules = |problem: &Problem, solver| {
+            (0..16)
+                .map(|seed| {
+                    problem
+                        .solve(&Options {
+                            solver,
+                            seed,
+                            ..Default::default()
+                        })
+                        .assignments
+                })
+                .collect::<HashSet<_>>()
+        };
+
+        for solver in [Solver::Greedy, Solver::Optimal] {
+            problem.constraints.jitter = 0.0;
+            assert_eq!(schedules(&problem, solver).len(), 1);
+
+            problem.constraints.jitter = 0.1;
+            assert!(schedules(&problem, solver).len() > 1, "{solver:?}");
+        }
+    }
+}
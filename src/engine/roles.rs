@@ -0,0 +1,42 @@
+use serde::Serialize;
+use souvenir::Id;
+
+use crate::Constraints;
+
+/// Least number of subjects holding a role that a slot needs.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub struct RoleRequirement {
+    pub role: Id,
+    pub count: u32,
+}
+
+/// A role requirement a generated slot falls short of.
+#[derive(Debug, Clone, Serialize)]
+pub struct Unmet {
+    pub slot: Id,
+    pub w2m_id: i32,
+    pub role: Id,
+    pub required: u32,
+    pub assigned: u32,
+}
+
+impl Constraints {
+    pub fn holds(&self, subject: Id, role: Id) -> bool {
+        self.roles
+            .get(&subject)
+            .is_some_and(|roles| roles.contains(&role))
+    }
+
+    /// How many of `members` hold `role`.
+    pub fn holders(&self, role: Id, members: &[Id]) -> u32 {
+        members.iter().filter(|&&s| self.holds(s, role)).count() as u32
+    }
+
+    /// Seats missing across `requirements` for a slot holding `members`.
+    pub fn shortfall(&self, requirements: &[RoleRequirement], members: &[Id]) -> u32 {
+        requirements
+            .iter()
+            .map(|r| r.count.saturating_sub(self.holders(r.role, members)))
+            .sum()
+    }
+}
@@ -0,0 +1,327 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Largest magnitude any scoring parameter may take, keeping scores well
+/// within what the solvers can weigh.
+const MAX_PARAMETER: f64 = 1e6;
+
+/// Largest power the default strategy may raise the load to.
+const MAX_EXPONENT: i32 = 8;
+
+/// Everything a scoring strategy knows about a candidate for a slot.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize)]
+pub struct Factors {
+    /// Schedules since the subject was last scheduled, or `None` if never.
+    pub weeks_since: Option<u64>,
+    /// Number of slots the subject marked available.
+    pub flexibility: u32,
+    /// Shifts held across the schedule and all of its ancestors, weighted
+    /// by the history decay.
+    pub total_shifted: f64,
+    /// Shifts already held in the schedule being generated.
+    pub shifts_current: u32,
+}
+
+impl Factors {
+    /// Factors as they read once the subject already holds `shifts` shifts in
+    /// the schedule being generated.
+    pub fn with_shifts(&self, shifts: u32) -> Self {
+        Self {
+            weeks_since: if shifts > 0 {
+                Some(0)
+            } else {
+                self.weeks_since
+            },
+            total_shifted: self.total_shifted + shifts as f64,
+            shifts_current: self.shifts_current + shifts,
+            ..*self
+        }
+    }
+}
+
+/// Ranks candidates for a slot. Higher scores are picked first.
+pub trait ScoringStrategy {
+    fn score(&self, factors: &Factors) -> f64;
+}
+
+/// The original fairness formula:
+///
+/// `weeks_since - 1 - flexibility / 20 - total_shifted / 5 - (2 + shifts_current)^3`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DefaultScoring {
+    /// Weeks assumed for subjects that have never been scheduled.
+    pub unscheduled_weeks: u64,
+    pub flexibility_divisor: f64,
+    pub history_divisor: f64,
+    pub load_offset: f64,
+    pub load_exponent: i32,
+}
+
+impl Default for DefaultScoring {
+    fn default() -> Self {
+        Self {
+            unscheduled_weeks: 100,
+            flexibility_divisor: 20.0,
+            history_divisor: 5.0,
+            load_offset: 2.0,
+            load_exponent: 3,
+        }
+    }
+}
+
+impl ScoringStrategy for DefaultScoring {
+    fn score(&self, factors: &Factors) -> f64 {
+        let weeks_since = factors.weeks_since.unwrap_or(self.unscheduled_weeks) as f64 - 1.0;
+
+        weeks_since
+            - factors.flexibility as f64 / self.flexibility_divisor
+            - factors.total_shifted / self.history_divisor
+            - (self.load_offset + factors.shifts_current as f64).powi(self.load_exponent)
+    }
+}
+
+/// A weighted sum of the factors, without the steep penalty on repeat
+/// shifts within a week.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LinearScoring {
+    /// Weeks assumed for subjects that have never been scheduled.
+    pub unscheduled_weeks: u64,
+    pub weeks_since: f64,
+    pub flexibility: f64,
+    pub total_shifted: f64,
+    pub shifts_current: f64,
+}
+
+impl Default for LinearScoring {
+    fn default() -> Self {
+        Self {
+            unscheduled_weeks: 100,
+            weeks_since: 1.0,
+            flexibility: -0.05,
+            total_shifted: -0.2,
+            shifts_current: -10.0,
+        }
+    }
+}
+
+impl ScoringStrategy for LinearScoring {
+    fn score(&self, factors: &Factors) -> f64 {
+        let weeks_since = factors.weeks_since.unwrap_or(self.unscheduled_weeks) as f64;
+
+        self.weeks_since * weeks_since
+            + self.flexibility * factors.flexibility as f64
+            + self.total_shifted * factors.total_shifted
+            + self.shifts_current * factors.shifts_current as f64
+    }
+}
+
+/// A scoring strategy together with its parameters, as read from the
+/// `[scheduler.scoring]` config section or a generate request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "strategy", rename_all = "snake_case")]
+pub enum Scoring {
+    Default(DefaultScoring),
+    Linear(LinearScoring),
+}
+
+impl Default for Scoring {
+    fn default() -> Self {
+        Self::Default(DefaultScoring::default())
+    }
+}
+
+impl Scoring {
+    /// Name of the strategy, as given in the `strategy` field.
+    pub fn strategy(&self) -> &'static str {
+        match self {
+            Scoring::Default(_) => "default",
+            Scoring::Linear(_) => "linear",
+        }
+    }
+
+    /// Applies per-run overrides on top of these settings. Parameters left out
+    /// of `overrides` keep their current values as long as the strategy is
+    /// unchanged; switching strategy starts from that strategy's defaults.
+    pub fn merge(&self, overrides: Value) -> anyhow::Result<Self> {
+        let mut merged = serde_json::to_value(self)?;
+
+        match (&mut merged, overrides) {
+            (Value::Object(base), Value::Object(overrides)) => {
+                if overrides
+                    .get("strategy")
+                    .is_some_and(|strategy| Some(strategy) != base.get("strategy"))
+                {
+                    base.clear();
+                }
+
+                base.extend(overrides);
+            }
+            (_, Value::Null) => {}
+            _ => anyhow::bail!("scoring overrides must be an object"),
+        }
+
+        let merged: Self = serde_json::from_value(merged)?;
+        merged.validate()?;
+
+        Ok(merged)
+    }
+
+    /// Rejects parameters that would make scores infinite or undefined.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let (weeks, parameters, divisors) = match self {
+            Scoring::Default(scoring) => {
+                anyhow::ensure!(
+                    (0..=MAX_EXPONENT).contains(&scoring.load_exponent),
+                    "load_exponent must be between 0 and {MAX_EXPONENT}"
+                );
+
+                (
+                    scoring.unscheduled_weeks,
+                    vec![
+                        ("flexibility_divisor", scoring.flexibility_divisor),
+                        ("history_divisor", scoring.history_divisor),
+                        ("load_offset", scoring.load_offset),
+                    ],
+                    vec![
+                        ("flexibility_divisor", scoring.flexibility_divisor),
+                        ("history_divisor", scoring.history_divisor),
+                    ],
+                )
+            }
+            Scoring::Linear(scoring) => (
+                scoring.unscheduled_weeks,
+                vec![
+                    ("weeks_since", scoring.weeks_since),
+                    ("flexibility", scoring.flexibility),
+                    ("total_shifted", scoring.total_shifted),
+                    ("shifts_current", scoring.shifts_current),
+                ],
+                vec![],
+            ),
+        };
+
+        anyhow::ensure!(
+            weeks as f64 <= MAX_PARAMETER,
+            "unscheduled_weeks must be at most {MAX_PARAMETER}"
+        );
+
+        for (name, value) in parameters {
+            anyhow::ensure!(
+                value.is_finite() && value.abs() <= MAX_PARAMETER,
+                "{name} must be a number between -{MAX_PARAMETER} and {MAX_PARAMETER}"
+            );
+        }
+
+        for (name, value) in divisors {
+            anyhow::ensure!(
+                value.abs() >= 1.0 / MAX_PARAMETER,
+                "{name} must not be zero"
+            );
+        }
+
+        Ok(())
+    }
+}
+
+impl ScoringStrategy for Scoring {
+    fn score(&self, factors: &Factors) -> f64 {
+        match self {
+            Scoring::Default(scoring) => scoring.score(factors),
+            Scoring::Linear(scoring) => scoring.score(factors),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn merge_keeps_parameters_of_the_same_strategy() {
+        let merged = Scoring::default()
+            .merge(json!({ "history_divisor": 10.0 }))
+            .unwrap();
+
+        let Scoring::Default(scoring) = merged else {
+            panic!("strategy changed");
+        };
+
+        assert_eq!(scoring.history_divisor, 10.0);
+        assert_eq!(scoring.flexibility_divisor, 20.0);
+    }
+
+    #[test]
+    fn merge_starts_a_new_strategy_from_its_defaults() {
+        let base = Scoring::Linear(LinearScoring {
+            flexibility: 3.0,
+            ..Default::default()
+        });
+
+        let merged = Scoring::default().merge(json!({ "strategy": "linear" }));
+        let Ok(Scoring::Linear(scoring)) = merged else {
+            panic!("strategy not switched");
+        };
+        assert_eq!(scoring.flexibility, -0.05);
+
+        let merged = base.merge(json!({ "weeks_since": 2.0 }));
+        let Ok(Scoring::Linear(scoring)) = merged else {
+            panic!("strategy changed");
+        };
+        assert_eq!(scoring.flexibility, 3.0);
+    }
+
+    #[test]
+    fn merge_rejects_parameters_that_break_scores() {
+        let scoring = Scoring::default();
+
+        for overrides in [
+            json!({ "history_divisor": 0.0 }),
+            json!({ "flexibility_divisor": 1e-9 }),
+            json!({ "load_offset": 1e12 }),
+            json!({ "load_exponent": 40 }),
+            json!({ "unscheduled_weeks": u64::MAX }),
+            json!(["not", "an", "object"]),
+        ] {
+            assert!(scoring.merge(overrides.clone()).is_err(), "{overrides}");
+        }
+    }
+
+    #[test]
+    fn validate_rejects_non_finite_parameters() {
+        let scoring = Scoring::Linear(LinearScoring {
+            total_shifted: f64::NAN,
+            ..Default::default()
+        });
+        assert!(scoring.validate().is_err());
+
+        let scoring = Scoring::Default(DefaultScoring {
+            load_offset: f64::INFINITY,
+            ..Default::default()
+        });
+        assert!(scoring.validate().is_err());
+    }
+
+    #[test]
+    fn default_scoring_prefers_fresh_and_rarely_scheduled_subjects() {
+        let scoring = DefaultScoring::default();
+        let factors = Factors {
+            weeks_since: Some(3),
+            flexibility: 10,
+            total_shifted: 5.0,
+            shifts_current: 0,
+        };
+
+        assert_eq!(scoring.score(&factors), 3.0 - 1.0 - 0.5 - 1.0 - 8.0);
+        assert!(scoring.score(&factors.with_shifts(1)) < scoring.score(&factors));
+        assert!(
+            scoring.score(&Factors {
+                weeks_since: None,
+                ..factors
+            }) > scoring.score(&factors)
+        );
+    }
+}
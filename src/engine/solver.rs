@@ -0,0 +1,346 @@
+use std::collections::{BTreeSet, HashMap};
+
+use serde::{Deserialize, Serialize};
+use souvenir::Id;
+
+use super::flow::Network;
+use crate::{Constraints, Exclusion, Factors, Ledger, RoleRequirement, ScoringStrategy, Staffing};
+
+/// Algorithm used to fill the slots of a new schedule.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Solver {
+    /// Walk slots from least to most flexible, taking the best-scored
+    /// candidates for each.
+    #[default]
+    Greedy,
+    /// Maximize the total score over the whole schedule as a min-cost flow.
+    Optimal,
+}
+
+/// A slot to be filled and the subjects available for it.
+#[derive(Clone, Debug)]
+pub struct Demand {
+    pub slot: Id,
+    /// Start of the slot, as a unix timestamp.
+    pub start: i64,
+    pub staffing: Staffing,
+    pub requirements: Vec<RoleRequirement>,
+    pub candidates: Vec<Id>,
+}
+
+/// Scores are scaled to integers before being used as flow costs.
+const SCALE: f64 = 1000.0;
+
+/// Cost bonus on minimum seats, large enough that no amount of score makes
+/// it worth leaving a minimum seat empty to fill an optional one.
+const REQUIRED: i64 = 1 << 40;
+
+/// Largest cost a single score may take. Steeper scores are clamped so they
+/// never outweigh a minimum seat, however many add up along a path.
+const MAX_COST: f64 = (REQUIRED >> 8) as f64;
+
+/// Places `pins`, then fills demands in order, giving each slot up to its
+/// maximum headcount of the best-scored candidates at that point.
+pub fn greedy(
+    pins: &[(Id, Id)],
+    demands: &[Demand],
+    baseline: &HashMap<Id, Factors>,
+    constraints: &Constraints,
+    scoring: &impl ScoringStrategy,
+) -> Vec<(Id, Id)> {
+    fill(pins.to_vec(), demands, baseline, constraints, scoring)
+}
+
+/// Tops up every slot that has seats left after `assignments`, walking
+/// demands in order the same way [`greedy`] does.
+fn fill(
+    mut assignments: Vec<(Id, Id)>,
+    demands: &[Demand],
+    baseline: &HashMap<Id, Factors>,
+    constraints: &Constraints,
+    scoring: &impl ScoringStrategy,
+) -> Vec<(Id, Id)> {
+    let starts: HashMap<Id, i64> = demands.iter().map(|d| (d.slot, d.start)).collect();
+    let mut ledger = Ledger::default();
+    let mut taken: HashMap<Id, Vec<Id>> = HashMap::new();
+
+    for &(slot, subject) in assignments.iter() {
+        ledger.add(subject, starts[&slot]);
+        taken.entry(slot).or_default().push(subject);
+    }
+
+    for demand in demands {
+        let mut seated = taken.remove(&demand.slot).unwrap_or_default();
+
+        let mut candidates: Vec<(Id, f64)> = demand
+            .candidates
+            .iter()
+            .filter(|subject| !seated.contains(subject))
+            .filter_map(|subject| {
+                let factors = baseline.get(subject)?;
+                constraints.admits(*subject, demand.start, &ledger).ok()?;
+
+                let score = scoring.score(&factors.with_shifts(ledger.count(*subject)))
+                    + constraints.bonus(*subject, demand.start, &ledger);
+
+                Some((*subject, score))
+            })
+            .collect();
+
+        candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        let pool: Vec<Id> = candidates.into_iter().map(|(subject, _)| subject).collect();
+
+        let max = demand.staffing.max as usize;
+
+        for subject in seat(&pool, &mut seated, max, &demand.requirements, constraints) {
+            ledger.add(subject, demand.start);
+            assignments.push((demand.slot, subject));
+        }
+    }
+
+    assignments
+}
+
+/// Seats subjects from `pool`, taken in order of preference, into a slot
+/// already holding `seated` until it holds `max`. Holders of required roles
+/// are seated first, then anyone. Returns the newly seated subjects.
+fn seat(
+    pool: &[Id],
+    seated: &mut Vec<Id>,
+    max: usize,
+    requirements: &[RoleRequirement],
+    constraints: &Constraints,
+) -> Vec<Id> {
+    let start = seated.len();
+
+    for requirement in requirements {
+        while constraints.holders(requirement.role, seated) < requirement.count
+            && seat_one(pool, seated, max, constraints, |subject| {
+                constraints.holds(subject, requirement.role)
+            })
+        {}
+    }
+
+    while seat_one(pool, seated, max, constraints, |_| true) {}
+
+    seated[start..].to_vec()
+}
+
+/// Seats the first subject in `pool` who is `eligible` and may join the slot
+/// under the pairing rules. A subject who must be paired and has no partner
+/// seated yet is seated together with the first partner in `pool` who may
+/// join. Returns whether anyone was seated.
+fn seat_one(
+    pool: &[Id],
+    seated: &mut Vec<Id>,
+    max: usize,
+    constraints: &Constraints,
+    eligible: impl Fn(Id) -> bool,
+) -> bool {
+    if seated.len() >= max {
+        return false;
+    }
+
+    for &subject in pool {
+        if seated.contains(&subject) || !eligible(subject) {
+            continue;
+        }
+
+        match constraints.pairs_with(subject, seated) {
+            Ok(()) => {
+                seated.push(subject);
+                return true;
+            }
+            Err(Exclusion::MustPair) if seated.len() + 2 <= max => {
+                seated.push(subject);
+
+                let partner = pool.iter().copied().find(|&partner| {
+                    !seated.contains(&partner)
+                        && constraints.partners(subject).any(|p| p == partner)
+                        && constraints.pairs_with(partner, seated).is_ok()
+                });
+
+                if let Some(partner) = partner {
+                    seated.push(partner);
+                    return true;
+                }
+
+                seated.pop();
+            }
+            Err(_) => {}
+        }
+    }
+
+    false
+}
+
+/// Places `pins`, then assigns subjects to the remaining seats so that as
+/// many seats as possible are filled, minimum seats before optional ones,
+/// and the summed score of every assignment is maximal. A subject's `n`th
+/// shift, pins included, is scored as if they held `n - 1` shifts already,
+/// so `baseline` should describe subjects before any shift in the new
+/// schedule.
+///
+/// Shift caps in `constraints` bound how many seats each subject can take.
+/// Limits that depend on when slots are, pairing rules and role
+/// requirements cannot be expressed as a flow, so assignments breaking them
+/// are swapped or dropped in slot order afterwards and the freed seats
+/// refilled greedily. The contiguity bonus only applies to that refill.
+///
+/// The result is exact when a strategy's score never increases with
+/// `shifts_current`; otherwise each subject's scores are taken in descending
+/// order.
+pub fn optimal(
+    pins: &[(Id, Id)],
+    demands: &[Demand],
+    baseline: &HashMap<Id, Factors>,
+    constraints: &Constraints,
+    scoring: &impl ScoringStrategy,
+) -> Vec<(Id, Id)> {
+    let mut network = Network::default();
+    let source = network.node();
+    let sink = network.node();
+
+    let subjects: BTreeSet<Id> = demands
+        .iter()
+        .flat_map(|demand| demand.candidates.iter().copied())
+        .filter(|subject| baseline.contains_key(subject))
+        .collect();
+
+    let subject_nodes: HashMap<Id, usize> = subjects
+        .iter()
+        .map(|&subject| (subject, network.node()))
+        .collect();
+
+    let mut pinned_slots: HashMap<Id, u32> = HashMap::new();
+    let mut pinned_subjects: HashMap<Id, u32> = HashMap::new();
+
+    for &(slot, subject) in pins {
+        *pinned_slots.entry(slot).or_default() += 1;
+        *pinned_subjects.entry(subject).or_default() += 1;
+    }
+
+    let mut availability: HashMap<Id, u32> = HashMap::new();
+    let mut candidate_edges = Vec::new();
+
+    for demand in demands {
+        let node = network.node();
+        let pinned = pinned_slots.get(&demand.slot).copied().unwrap_or(0);
+        let min = demand.staffing.min.saturating_sub(pinned);
+        let optional = demand
+            .staffing
+            .max
+            .saturating_sub(pinned)
+            .saturating_sub(min);
+
+        network.edge(source, node, min as i64, -REQUIRED);
+        network.edge(source, node, optional as i64, 0);
+
+        for subject in demand.candidates.iter() {
+            if pins.contains(&(demand.slot, *subject)) {
+                continue;
+            }
+
+            if let Some(&subject_node) = subject_nodes.get(subject) {
+                let jitter = constraints.perturbation(*subject, demand.start);
+                let edge = network.edge(node, subject_node, 1, -(jitter * SCALE).round() as i64);
+                candidate_edges.push((demand.slot, *subject, edge));
+                *availability.entry(*subject).or_default() += 1;
+            }
+        }
+    }
+
+    for subject in subjects.iter() {
+        let node = subject_nodes[subject];
+        let factors = baseline[subject];
+        let pinned = pinned_subjects.get(subject).copied().unwrap_or(0);
+        let available = availability.get(subject).copied().unwrap_or(0);
+
+        let seats = constraints
+            .shift_cap(*subject)
+            .map_or(available, |cap| cap.saturating_sub(pinned).min(available));
+
+        let mut scores: Vec<f64> = (pinned..pinned + available)
+            .map(|shifts| {
+                scoring.score(&factors.with_shifts(shifts)) + constraints.steer(*subject, shifts)
+            })
+            .collect();
+
+        scores.sort_by(|a, b| b.total_cmp(a));
+        scores.truncate(seats as usize);
+
+        for score in scores {
+            let cost = -(score * SCALE).round().clamp(-MAX_COST, MAX_COST);
+            network.edge(node, sink, 1, cost as i64);
+        }
+    }
+
+    network.solve(source, sink);
+
+    let starts: HashMap<Id, i64> = demands.iter().map(|d| (d.slot, d.start)).collect();
+    let by_slot: HashMap<Id, &Demand> = demands.iter().map(|d| (d.slot, d)).collect();
+
+    let mut flowed: Vec<(Id, Id)> = candidate_edges
+        .into_iter()
+        .filter(|&(_, _, edge)| network.flow(edge) > 0)
+        .map(|(slot, subject, _)| (slot, subject))
+        .collect();
+
+    flowed.sort_by_key(|&(slot, _)| (starts[&slot], slot));
+
+    let mut ledger = Ledger::default();
+    let mut assignments = pins.to_vec();
+
+    for &(slot, subject) in pins {
+        ledger.add(subject, starts[&slot]);
+    }
+
+    for group in flowed.chunk_by(|a, b| a.0 == b.0) {
+        let demand = by_slot[&group[0].0];
+        let flowed: Vec<Id> = group.iter().map(|&(_, subject)| subject).collect();
+
+        let admitted = |subject: &Id| {
+            baseline.contains_key(subject)
+                && constraints.admits(*subject, demand.start, &ledger).is_ok()
+        };
+
+        let mut pool: Vec<Id> = flowed.iter().copied().filter(admitted).collect();
+        let mut seated: Vec<Id> = pins
+            .iter()
+            .filter(|&&(slot, _)| slot == demand.slot)
+            .map(|&(_, subject)| subject)
+            .collect();
+        let seats = seated.len() + pool.len();
+
+        // Holders of required roles the flow left out, should the slot
+        // need them.
+        let mut spare: Vec<(Id, f64)> = demand
+            .candidates
+            .iter()
+            .filter(|subject| !flowed.contains(subject) && !seated.contains(subject))
+            .filter(|subject| admitted(subject))
+            .map(|&subject| {
+                let held = ledger.count(subject);
+                let factors = baseline[&subject].with_shifts(held);
+                (
+                    subject,
+                    scoring.score(&factors)
+                        + constraints.steer(subject, held)
+                        + constraints.perturbation(subject, demand.start),
+                )
+            })
+            .collect();
+
+        spare.sort_by(|a, b| b.1.total_cmp(&a.1));
+        pool.extend(spare.into_iter().map(|(subject, _)| subject));
+
+        for subject in seat(&pool, &mut seated, seats, &demand.requirements, constraints) {
+            ledger.add(subject, demand.start);
+            assignments.push((demand.slot, subject));
+        }
+    }
+
+    fill(assignments, demands, baseline, constraints, scoring)
+}
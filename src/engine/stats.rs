@@ -0,0 +1,175 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use souvenir::Id;
+
+/// Shift counts for one subject.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Tally {
+    /// Shifts in the schedule being measured.
+    pub current: u32,
+    /// Shifts across the schedule and all of its ancestors.
+    pub total: u32,
+}
+
+/// How evenly shifts are spread across subjects.
+#[derive(Debug, Clone, Serialize)]
+pub struct Fairness {
+    /// Subjects holding more than one shift in the schedule.
+    pub double_shifts: u32,
+    /// Mean of total shifts per subject.
+    pub mean: Option<f64>,
+    /// Sample standard deviation of total shifts per subject.
+    pub stddev: Option<f64>,
+    /// Standard deviation of total shifts among subjects who have worked.
+    pub nonzero_stddev: Option<f64>,
+}
+
+impl Fairness {
+    pub fn measure(tallies: &[Tally]) -> Self {
+        let totals: Vec<f64> = tallies.iter().map(|t| t.total as f64).collect();
+        let nonzero: Vec<f64> = totals.iter().copied().filter(|&t| t > 0.0).collect();
+
+        Self {
+            double_shifts: tallies.iter().filter(|t| t.current > 1).count() as u32,
+            mean: mean(&totals),
+            stddev: stddev(&totals),
+            nonzero_stddev: stddev(&nonzero),
+        }
+    }
+}
+
+/// Shift statistics for a schedule, over every subject considered.
+#[derive(Debug, Clone, Serialize)]
+pub struct Statistics {
+    #[serde(flatten)]
+    pub fairness: Fairness,
+    /// Gini coefficient of total shifts per subject: 0 when everyone has
+    /// worked equally often, approaching 1 as shifts concentrate on few.
+    pub gini: Option<f64>,
+    /// Most total shifts held by any subject.
+    pub max_shifts: Option<u32>,
+    /// Fewest total shifts held by any subject.
+    pub min_shifts: Option<u32>,
+    /// Subjects without a single shift in the schedule or its ancestry.
+    pub never_scheduled: Vec<Id>,
+    /// Subjects by total shifts held, fewest first.
+    pub histogram: Vec<Bucket>,
+}
+
+/// Subjects holding the same number of total shifts.
+#[derive(Debug, Clone, Serialize)]
+pub struct Bucket {
+    pub shifts: u32,
+    pub subjects: Vec<Id>,
+}
+
+impl Statistics {
+    pub fn measure(tallies: &[(Id, Tally)]) -> Self {
+        let totals: Vec<f64> = tallies.iter().map(|(_, t)| t.total as f64).collect();
+        let mut buckets: BTreeMap<u32, Vec<Id>> = BTreeMap::new();
+
+        for &(subject, tally) in tallies {
+            buckets.entry(tally.total).or_default().push(subject);
+        }
+
+        for subjects in buckets.values_mut() {
+            subjects.sort();
+        }
+
+        Self {
+            fairness: Fairness::measure(
+                &tallies.iter().map(|&(_, tally)| tally).collect::<Vec<_>>(),
+            ),
+            gini: gini(&totals),
+            max_shifts: tallies.iter().map(|(_, t)| t.total).max(),
+            min_shifts: tallies.iter().map(|(_, t)| t.total).min(),
+            never_scheduled: buckets.get(&0).cloned().unwrap_or_default(),
+            histogram: buckets
+                .into_iter()
+                .map(|(shifts, subjects)| Bucket { shifts, subjects })
+                .collect(),
+        }
+    }
+}
+
+pub fn mean(data: &[f64]) -> Option<f64> {
+    if data.is_empty() {
+        return None;
+    }
+
+    Some(data.iter().sum::<f64>() / data.len() as f64)
+}
+
+pub fn stddev(data: &[f64]) -> Option<f64> {
+    if data.len() < 2 {
+        return None;
+    }
+
+    let mean = mean(data)?;
+    let var = data.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / (data.len() as f64 - 1.0);
+
+    Some(var.sqrt())
+}
+
+pub fn gini(data: &[f64]) -> Option<f64> {
+    let sum: f64 = data.iter().sum();
+
+    if data.is_empty() || sum <= 0.0 {
+        return None;
+    }
+
+    let mut sorted = data.to_vec();
+    sorted.sort_by(f64::total_cmp);
+
+    let n = sorted.len() as f64;
+    let ranked: f64 = sorted
+        .iter()
+        .enumerate()
+        .map(|(i, x)| (i as f64 + 1.0) * x)
+        .sum();
+
+    Some(2.0 * ranked / (n * sum) - (n + 1.0) / n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gini_is_zero_when_shifts_are_even() {
+        assert_eq!(gini(&[3.0, 3.0, 3.0]), Some(0.0));
+    }
+
+    #[test]
+    fn gini_grows_as_shifts_concentrate() {
+        let spread = gini(&[1.0, 2.0, 3.0, 4.0]).unwrap();
+        let concentrated = gini(&[0.0, 0.0, 0.0, 4.0]).unwrap();
+
+        assert!((spread - 0.25).abs() < 1e-9);
+        assert!((concentrated - 0.75).abs() < 1e-9);
+    }
+
+    #[test]
+    fn gini_is_undefined_without_shifts() {
+        assert_eq!(gini(&[]), None);
+        assert_eq!(gini(&[0.0, 0.0]), None);
+    }
+
+    #[test]
+    fn stddev_needs_two_values() {
+        assert_eq!(mean(&[]), None);
+        assert_eq!(stddev(&[4.0]), None);
+        assert_eq!(stddev(&[2.0, 4.0]), Some(2f64.sqrt()));
+    }
+
+    #[test]
+    fn fairness_counts_double_shifts_in_the_schedule() {
+        let tally = |current, total| Tally { current, total };
+        let fairness = Fairness::measure(&[tally(2, 2), tally(1, 5), tally(0, 0)]);
+
+        assert_eq!(fairness.double_shifts, 1);
+        assert_eq!(fairness.mean, Some(7.0 / 3.0));
+        assert_eq!(fairness.nonzero_stddev, stddev(&[2.0, 5.0]));
+    }
+}
@@ -0,0 +1,197 @@
+use serde::Serialize;
+use souvenir::Id;
+
+use crate::Constraints;
+
+/// Shifts a subject should hold in the schedule being generated. Unlike
+/// shift caps, targets are steered toward rather than enforced.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ShiftTarget {
+    pub min: Option<u32>,
+    pub max: Option<u32>,
+}
+
+/// How far a subject's shifts land from their target.
+#[derive(Debug, Clone, Serialize)]
+pub struct Deviation {
+    pub subject: Id,
+    #[serde(flatten)]
+    pub target: ShiftTarget,
+    pub assigned: u32,
+    /// Shifts short of `min` as a negative number, shifts past `max` as a
+    /// positive one, or zero when on target.
+    pub deviation: i64,
+}
+
+impl ShiftTarget {
+    pub fn deviation(&self, assigned: u32) -> i64 {
+        let assigned = assigned as i64;
+
+        match (self.min, self.max) {
+            (Some(min), _) if assigned < min as i64 => assigned - min as i64,
+            (_, Some(max)) if assigned > max as i64 => assigned - max as i64,
+            _ => 0,
+        }
+    }
+
+    /// Tightens the target by what is left of a term's `term_min` and
+    /// `term_max` once `held` shifts are counted, with the minimum spread
+    /// evenly over the `weeks_left` schedules still to come.
+    pub fn paced(
+        self,
+        term_min: Option<u32>,
+        term_max: Option<u32>,
+        held: u32,
+        weeks_left: u32,
+    ) -> Self {
+        let pace = term_min.map(|total| total.saturating_sub(held).div_ceil(weeks_left.max(1)));
+        let remaining = term_max.map(|total| total.saturating_sub(held));
+
+        let min = match (self.min, pace) {
+            (Some(min), Some(pace)) => Some(min.max(pace)),
+            (min, pace) => min.or(pace),
+        };
+        let max = match (self.max, remaining) {
+            (Some(max), Some(remaining)) => Some(max.min(remaining)),
+            (max, remaining) => max.or(remaining),
+        };
+
+        // The term maximum wins over a minimum it no longer leaves room for.
+        Self {
+            min: match (min, max) {
+                (Some(min), Some(max)) => Some(min.min(max)),
+                (min, _) => min,
+            },
+            max,
+        }
+    }
+}
+
+impl Constraints {
+    /// Preference for giving a subject who already holds `held` shifts
+    /// another: `target_weight` while below their target, its negative once
+    /// at or past it, and nothing otherwise.
+    pub fn steer(&self, subject: Id, held: u32) -> f64 {
+        let Some(target) = self.targets.get(&subject) else {
+            return 0.0;
+        };
+
+        if target.min.is_some_and(|min| held < min) {
+            self.target_weight
+        } else if target.max.is_some_and(|max| held >= max) {
+            -self.target_weight
+        } else {
+            0.0
+        }
+    }
+
+    /// Deviation from target of every subject with one, given how many
+    /// shifts each holds.
+    pub fn deviations(&self, assigned: impl Fn(Id) -> u32) -> Vec<Deviation> {
+        let mut deviations: Vec<Deviation> = self
+            .targets
+            .iter()
+            .map(|(&subject, &target)| {
+                let assigned = assigned(subject);
+
+                Deviation {
+                    subject,
+                    target,
+                    assigned,
+                    deviation: target.deviation(assigned),
+                }
+            })
+            .collect();
+
+        deviations.sort_by_key(|deviation| deviation.subject);
+        deviations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use souvenir::id;
+
+    use super::*;
+    use crate::Subject;
+
+    fn target(min: Option<u32>, max: Option<u32>) -> ShiftTarget {
+        ShiftTarget { min, max }
+    }
+
+    #[test]
+    fn deviation_is_signed_distance_from_the_target() {
+        let target = target(Some(2), Some(4));
+
+        assert_eq!(target.deviation(0), -2);
+        assert_eq!(target.deviation(3), 0);
+        assert_eq!(target.deviation(6), 2);
+    }
+
+    #[test]
+    fn paced_spreads_the_term_minimum_over_the_weeks_left() {
+        let weekly = target(None, None);
+
+        assert_eq!(weekly.paced(Some(5), None, 1, 2), target(Some(2), None));
+        assert_eq!(weekly.paced(Some(5), None, 1, 3), target(Some(2), None));
+        assert_eq!(weekly.paced(Some(5), None, 6, 3), target(Some(0), None));
+        assert_eq!(weekly.paced(Some(5), None, 0, 0), target(Some(5), None));
+        assert_eq!(
+            target(Some(3), None).paced(Some(5), None, 1, 2),
+            target(Some(3), None)
+        );
+    }
+
+    #[test]
+    fn paced_caps_at_what_is_left_of_the_term_maximum() {
+        assert_eq!(
+            target(None, Some(3)).paced(None, Some(4), 3, 2),
+            target(None, Some(1))
+        );
+        assert_eq!(
+            target(None, Some(3)).paced(None, Some(10), 3, 2),
+            target(None, Some(3))
+        );
+        assert_eq!(
+            target(None, None).paced(None, Some(4), 9, 2),
+            target(None, Some(0))
+        );
+        assert_eq!(
+            target(Some(1), Some(2)).paced(None, None, 9, 2),
+            target(Some(1), Some(2))
+        );
+    }
+
+    #[test]
+    fn paced_never_leaves_the_minimum_above_the_maximum() {
+        assert_eq!(
+            target(None, None).paced(Some(10), Some(4), 0, 2),
+            target(Some(4), Some(4))
+        );
+        assert_eq!(
+            target(Some(3), None).paced(None, Some(4), 3, 2),
+            target(Some(1), Some(1))
+        );
+        assert_eq!(
+            target(None, Some(1)).paced(Some(9), None, 0, 2),
+            target(Some(1), Some(1))
+        );
+    }
+
+    #[test]
+    fn steer_pulls_toward_the_target() {
+        let subject = id!(Subject);
+        let constraints = Constraints {
+            targets: HashMap::from([(subject, target(Some(1), Some(2)))]),
+            target_weight: 3.0,
+            ..Default::default()
+        };
+
+        assert_eq!(constraints.steer(subject, 0), 3.0);
+        assert_eq!(constraints.steer(subject, 1), 0.0);
+        assert_eq!(constraints.steer(subject, 2), -3.0);
+        assert_eq!(constraints.steer(id!(Subject), 0), 0.0);
+    }
+}
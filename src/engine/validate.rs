@@ -0,0 +1,476 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use souvenir::Id;
+
+use crate::{Exclusion, Ledger, PairingKind, Problem, SlotSpec};
+
+/// How serious a finding is.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// The schedule breaks a hard rule.
+    Error,
+    /// The schedule breaks a preference, or looks unintended.
+    Warning,
+    /// Worth knowing, but deliberate.
+    Info,
+}
+
+/// What a finding is about.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Check {
+    /// The subject did not mark themselves available for the slot.
+    Unavailable,
+    /// The subject has left.
+    Inactive,
+    /// The slot falls in the subject's time off.
+    TimeOff,
+    /// The slot is assigned but not a known slot.
+    UnknownSlot,
+    /// Two slots start at the same time.
+    Duplicate,
+    /// The availability names a slot that is not a known slot.
+    MissingSlot,
+    /// Nobody is assigned to a slot somebody is available for.
+    Missing,
+    Understaffed,
+    Overstaffed,
+    ShiftCap,
+    DayCap,
+    RestGap,
+    MustPair,
+    NeverPair,
+    UnmetRole,
+}
+
+impl From<Exclusion> for Check {
+    fn from(exclusion: Exclusion) -> Self {
+        match exclusion {
+            Exclusion::ShiftCap => Check::ShiftCap,
+            Exclusion::DayCap => Check::DayCap,
+            Exclusion::RestGap => Check::RestGap,
+            Exclusion::MustPair => Check::MustPair,
+            Exclusion::NeverPair => Check::NeverPair,
+        }
+    }
+}
+
+/// One problem found in a schedule.
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding {
+    pub severity: Severity,
+    pub check: Check,
+    pub slot: Option<Id>,
+    pub subject: Option<Id>,
+    pub message: String,
+}
+
+impl Problem {
+    /// Checks existing assignments against the problem's availability and
+    /// constraints, rather than generating new ones, as well as the slots
+    /// themselves. Assignments listed in `pins` were agreed ahead of time, so
+    /// being unavailable for them is only reported for information.
+    pub fn validate(&self, assignments: &[(Id, Id)]) -> Vec<Finding> {
+        let constraints = &self.constraints;
+        let slots: HashMap<Id, &SlotSpec> = self.slots.iter().map(|s| (s.id, s)).collect();
+        let mut findings = Vec::new();
+        let mut members: HashMap<Id, Vec<Id>> = HashMap::new();
+        let mut known: Vec<(&SlotSpec, Id)> = Vec::new();
+
+        for &(slot, subject) in assignments {
+            let finding = |severity, check, message| Finding {
+                severity,
+                check,
+                slot: Some(slot),
+                subject: Some(subject),
+                message,
+            };
+
+            let Some(&spec) = slots.get(&slot) else {
+                findings.push(finding(
+                    Severity::Error,
+                    Check::UnknownSlot,
+                    format!("{slot} is not a known slot"),
+                ));
+                continue;
+            };
+
+            let available = self
+                .availability
+                .get(&slot)
+                .is_some_and(|subjects| subjects.contains(&subject));
+
+            if self.inactive.contains(&subject) {
+                findings.push(finding(
+                    Severity::Error,
+                    Check::Inactive,
+                    format!("{subject} has left but is assigned to {slot}"),
+                ));
+            } else if let Some(time_off) = self.time_off.get(&(slot, subject)) {
+                findings.push(finding(
+                    Severity::Error,
+                    Check::TimeOff,
+                    format!("{slot} falls in time off {time_off} of {subject}"),
+                ));
+            } else if !available {
+                let severity = if self.pins.contains(&(slot, subject)) {
+                    Severity::Info
+                } else {
+                    Severity::Error
+                };
+
+                findings.push(finding(
+                    severity,
+                    Check::Unavailable,
+                    format!("{subject} did not mark themselves available for {slot}"),
+                ));
+            }
+
+            members.entry(slot).or_default().push(subject);
+            known.push((spec, subject));
+        }
+
+        let mut starts: HashMap<i32, Id> = HashMap::new();
+
+        for spec in self.slots.iter() {
+            if let Some(first) = starts.insert(spec.w2m_id, spec.id) {
+                findings.push(Finding {
+                    severity: Severity::Error,
+                    check: Check::Duplicate,
+                    slot: Some(spec.id),
+                    subject: None,
+                    message: format!("{} starts at the same time as {first}", spec.id),
+                });
+            }
+        }
+
+        let mut unknown: Vec<Id> = self
+            .availability
+            .keys()
+            .copied()
+            .filter(|slot| !slots.contains_key(slot))
+            .collect();
+        unknown.sort();
+
+        for slot in unknown {
+            findings.push(Finding {
+                severity: Severity::Warning,
+                check: Check::MissingSlot,
+                slot: Some(slot),
+                subject: None,
+                message: format!("The availability names {slot}, which is not a known slot"),
+            });
+        }
+
+        // Replay shifts in time order so each limit is reported on the shift
+        // that goes past it.
+        known.sort_by_key(|(spec, _)| spec.w2m_id);
+        let mut ledger = Ledger::default();
+
+        for &(spec, subject) in known.iter() {
+            let start = spec.w2m_id as i64;
+
+            if let Err(exclusion) = constraints.admits(subject, start, &ledger) {
+                let message = match exclusion {
+                    Exclusion::ShiftCap => format!(
+                        "{subject} holds more than {} shifts",
+                        constraints.shift_cap(subject).unwrap_or_default()
+                    ),
+                    Exclusion::DayCap => format!(
+                        "{subject} holds more than {} shifts on the day of {}",
+                        constraints.max_shifts_per_day.unwrap_or_default(),
+                        spec.id
+                    ),
+                    _ => format!("{subject} has too little rest around {}", spec.id),
+                };
+
+                findings.push(Finding {
+                    severity: Severity::Error,
+                    check: exclusion.into(),
+                    slot: Some(spec.id),
+                    subject: Some(subject),
+                    message,
+                });
+            }
+
+            ledger.add(subject, start);
+        }
+
+        for spec in self.slots.iter() {
+            let members = members.get(&spec.id).map_or(&[][..], Vec::as_slice);
+            let assigned = members.len() as u32;
+            let finding = |severity, check, message| Finding {
+                severity,
+                check,
+                slot: Some(spec.id),
+                subject: None,
+                message,
+            };
+
+            // Slots outside the availability, such as pinned ones from
+            // another week, are not expected to be filled.
+            let wanted = self.availability.contains_key(&spec.id)
+                && (self.available(spec.id).next().is_some() || spec.staffing.min > 0);
+
+            if assigned == 0 && wanted {
+                let severity = if spec.staffing.min > 0 {
+                    Severity::Error
+                } else {
+                    Severity::Warning
+                };
+
+                findings.push(finding(
+                    severity,
+                    Check::Missing,
+                    format!("Nobody is assigned to {}", spec.id),
+                ));
+            } else if assigned > 0 && assigned < spec.staffing.min {
+                findings.push(finding(
+                    Severity::Error,
+                    Check::Understaffed,
+                    format!(
+                        "{} has {assigned} of at least {} subjects",
+                        spec.id, spec.staffing.min
+                    ),
+                ));
+            } else if assigned > spec.staffing.max {
+                findings.push(finding(
+                    Severity::Warning,
+                    Check::Overstaffed,
+                    format!(
+                        "{} has {assigned} of at most {} subjects",
+                        spec.id, spec.staffing.max
+                    ),
+                ));
+            }
+
+            for requirement in spec.requirements.iter() {
+                let holders = constraints.holders(requirement.role, members);
+
+                if holders < requirement.count {
+                    findings.push(finding(
+                        Severity::Warning,
+                        Check::UnmetRole,
+                        format!(
+                            "{} has {holders} of at least {} holders of {}",
+                            spec.id, requirement.count, requirement.role
+                        ),
+                    ));
+                }
+            }
+
+            for pairing in constraints.broken(members) {
+                let message = match pairing.kind {
+                    PairingKind::MustPair => format!(
+                        "{} works {} without a required partner",
+                        pairing.subject, spec.id
+                    ),
+                    PairingKind::NeverPair => format!(
+                        "{} and {} share {}",
+                        pairing.subject, pairing.partner, spec.id
+                    ),
+                };
+
+                findings.push(Finding {
+                    severity: Severity::Warning,
+                    check: match pairing.kind {
+                        PairingKind::MustPair => Check::MustPair,
+                        PairingKind::NeverPair => Check::NeverPair,
+                    },
+                    slot: Some(spec.id),
+                    subject: Some(pairing.subject),
+                    message,
+                });
+            }
+        }
+
+        findings.sort_by_key(|finding| finding.severity);
+        findings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use souvenir::id;
+
+    use super::*;
+    use crate::{Slot, Staffing, Subject, TimeOff};
+
+    fn slot(w2m_id: i32, min: u32, max: u32) -> SlotSpec {
+        SlotSpec {
+            id: id!(Slot),
+            w2m_id,
+            staffing: Staffing { min, max },
+            requirements: Vec::new(),
+        }
+    }
+
+    fn checks(findings: &[Finding]) -> Vec<(Severity, Check)> {
+        findings.iter().map(|f| (f.severity, f.check)).collect()
+    }
+
+    #[test]
+    fn accepts_a_schedule_that_breaks_nothing() {
+        let (a, b) = (id!(Subject), id!(Subject));
+        let only = slot(0, 1, 2);
+        let problem = Problem {
+            availability: HashMap::from([(only.id, vec![a, b])]),
+            slots: vec![only.clone()],
+            ..Default::default()
+        };
+
+        assert!(problem.validate(&[(only.id, a), (only.id, b)]).is_empty());
+    }
+
+    #[test]
+    fn reports_why_a_subject_should_not_hold_a_slot() {
+        let [available, unavailable, pinned, left, away] = [(); 5].map(|_| id!(Subject));
+        let only = slot(0, 0, 5);
+        let off = id!(TimeOff);
+        let problem = Problem {
+            availability: HashMap::from([(only.id, vec![available, left, away])]),
+            slots: vec![only.clone()],
+            pins: vec![(only.id, pinned)],
+            inactive: HashSet::from([left]),
+            time_off: HashMap::from([((only.id, away), off)]),
+            ..Default::default()
+        };
+
+        let findings = problem.validate(&[
+            (only.id, available),
+            (only.id, unavailable),
+            (only.id, pinned),
+            (only.id, left),
+            (only.id, away),
+        ]);
+        let about = |subject: Id| {
+            findings
+                .iter()
+                .filter(|f| f.subject == Some(subject))
+                .map(|f| (f.severity, f.check))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(about(available), vec![]);
+        assert_eq!(
+            about(unavailable),
+            vec![(Severity::Error, Check::Unavailable)]
+        );
+        assert_eq!(about(pinned), vec![(Severity::Info, Check::Unavailable)]);
+        assert_eq!(about(left), vec![(Severity::Error, Check::Inactive)]);
+        assert_eq!(about(away), vec![(Severity::Error, Check::TimeOff)]);
+    }
+
+    #[test]
+    fn reports_slots_that_do_not_line_up() {
+        let subject = id!(Subject);
+        let (first, twin) = (slot(0, 0, 1), slot(0, 0, 1));
+        let (stray, unknown) = (id!(Slot), id!(Slot));
+        let problem = Problem {
+            availability: HashMap::from([(stray, vec![subject])]),
+            slots: vec![first, twin.clone()],
+            ..Default::default()
+        };
+
+        let findings = problem.validate(&[(unknown, subject)]);
+
+        assert_eq!(
+            checks(&findings),
+            vec![
+                (Severity::Error, Check::UnknownSlot),
+                (Severity::Error, Check::Duplicate),
+                (Severity::Warning, Check::MissingSlot),
+            ]
+        );
+        assert_eq!(findings[1].slot, Some(twin.id));
+        assert_eq!(findings[2].slot, Some(stray));
+    }
+
+    #[test]
+    fn reports_headcounts_outside_the_staffing() {
+        let people: Vec<Id> = (0..3).map(|_| id!(Subject)).collect();
+        let (empty, short, crowded) = (slot(0, 1, 1), slot(3600, 2, 2), slot(7200, 1, 1));
+        let problem = Problem {
+            availability: [&empty, &short, &crowded]
+                .iter()
+                .map(|slot| (slot.id, people.clone()))
+                .collect(),
+            slots: vec![empty.clone(), short.clone(), crowded.clone()],
+            ..Default::default()
+        };
+
+        let findings = problem.validate(&[
+            (short.id, people[0]),
+            (crowded.id, people[1]),
+            (crowded.id, people[2]),
+        ]);
+        let about = |slot: Id| {
+            findings
+                .iter()
+                .filter(|f| f.slot == Some(slot))
+                .map(|f| (f.severity, f.check))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(about(empty.id), vec![(Severity::Error, Check::Missing)]);
+        assert_eq!(
+            about(short.id),
+            vec![(Severity::Error, Check::Understaffed)]
+        );
+        assert_eq!(
+            about(crowded.id),
+            vec![(Severity::Warning, Check::Overstaffed)]
+        );
+    }
+
+    #[test]
+    fn reports_limits_on_the_shift_that_goes_past_them() {
+        let subject = id!(Subject);
+        let slots = [slot(0, 0, 1), slot(3600, 0, 1), slot(4 * 3600, 0, 1)];
+        let mut problem = Problem {
+            availability: slots.iter().map(|s| (s.id, vec![subject])).collect(),
+            slots: slots.to_vec(),
+            ..Default::default()
+        };
+        problem.constraints.max_shifts = Some(2);
+        problem.constraints.min_rest = 2 * 3600;
+
+        // Listed out of time order, yet each limit lands on the later shift.
+        let findings = problem.validate(&[
+            (slots[2].id, subject),
+            (slots[1].id, subject),
+            (slots[0].id, subject),
+        ]);
+
+        assert_eq!(
+            findings
+                .iter()
+                .map(|f| (f.check, f.slot.unwrap()))
+                .collect::<Vec<_>>(),
+            vec![
+                (Check::RestGap, slots[1].id),
+                (Check::ShiftCap, slots[2].id),
+            ]
+        );
+    }
+
+    #[test]
+    fn only_expects_slots_in_the_availability_to_be_filled() {
+        let subject = id!(Subject);
+        let (listed, other) = (slot(0, 1, 1), slot(3600, 1, 1));
+        let problem = Problem {
+            availability: HashMap::from([(listed.id, vec![subject])]),
+            slots: vec![listed.clone(), other],
+            ..Default::default()
+        };
+
+        let findings = problem.validate(&[]);
+
+        assert_eq!(checks(&findings), vec![(Severity::Error, Check::Missing)]);
+        assert_eq!(findings[0].slot, Some(listed.id));
+    }
+}
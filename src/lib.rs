@@ -2,10 +2,12 @@
 
 mod api;
 mod config;
+mod engine;
 mod model;
 
 pub use api::*;
 pub use config::*;
+pub use engine::*;
 pub use model::*;
 
 use sqlx::{Acquire, Postgres};
@@ -3,6 +3,8 @@ use std::collections::HashMap;
 use souvenir::{Id, Identifiable, Tagged};
 use sqlx::PgConnection;
 
+use crate::Slot;
+
 #[derive(Debug, Identifiable, Tagged)]
 #[souvenir(tag = "av")]
 pub struct Availability {
@@ -32,6 +34,19 @@ impl Availability {
         Ok(())
     }
 
+    pub async fn find(id: Id, tx: &mut PgConnection) -> anyhow::Result<Self> {
+        Ok(sqlx::query_as!(
+            Availability,
+            r#"
+                SELECT id as "id: _", name FROM availability
+                    WHERE id = $1;
+            "#,
+            id as Id,
+        )
+        .fetch_one(tx)
+        .await?)
+    }
+
     pub async fn fetch_current(tx: &mut PgConnection) -> anyhow::Result<Self> {
         Ok(sqlx::query_as!(
             Availability,
@@ -105,10 +120,8 @@ impl Availability {
         .collect())
     }
 
-    pub async fn sorted_by_flexibility(
-        &self,
-        tx: &mut PgConnection,
-    ) -> anyhow::Result<Vec<(Id, Vec<Id>)>> {
+    /// Subjects available for each slot.
+    pub async fn entries(&self, tx: &mut PgConnection) -> anyhow::Result<HashMap<Id, Vec<Id>>> {
         let mut map: HashMap<_, Vec<_>> = HashMap::new();
 
         sqlx::query!(
@@ -125,7 +138,39 @@ impl Availability {
         .map(|record| (record.slot, record.subject))
         .for_each(|(slot, subject)| map.entry(slot).or_default().push(subject));
 
-        let mut list = Vec::from_iter(map.into_iter());
+        Ok(map)
+    }
+
+    /// Slots anyone marked themselves available for, in time order.
+    pub async fn slots(&self, tx: &mut PgConnection) -> anyhow::Result<Vec<Slot>> {
+        Ok(sqlx::query!(
+            r#"
+                SELECT DISTINCT slot.id AS "id: Id", w2m_id, min_staff, max_staff
+                    FROM availability_entry
+                    INNER JOIN slot ON slot_id = slot.id
+                    WHERE availability_id = $1
+                    ORDER BY w2m_id;
+            "#,
+            self.id as Id
+        )
+        .fetch_all(tx)
+        .await?
+        .into_iter()
+        .map(|record| Slot {
+            id: record.id,
+            w2m_id: record.w2m_id.unwrap(),
+            min_staff: record.min_staff,
+            max_staff: record.max_staff,
+        })
+        .collect())
+    }
+
+    #[allow(clippy::useless_conversion, clippy::unnecessary_sort_by)]
+    pub async fn sorted_by_flexibility(
+        &self,
+        tx: &mut PgConnection,
+    ) -> anyhow::Result<Vec<(Id, Vec<Id>)>> {
+        let mut list = Vec::from_iter(self.entries(tx).await?.into_iter());
         list.sort_by(|a, b| a.1.len().cmp(&b.1.len()));
 
         Ok(list)
@@ -1,9 +1,19 @@
 mod availability;
+mod pairing;
+mod role;
 mod schedule;
 mod slot;
 mod subject;
+mod target;
+mod term;
+mod time_off;
 
 pub use availability::*;
+pub use pairing::*;
+pub use role::*;
 pub use schedule::*;
 pub use slot::*;
 pub use subject::*;
+pub use target::*;
+pub use term::*;
+pub use time_off::*;
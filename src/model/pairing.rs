@@ -0,0 +1,104 @@
+use souvenir::{id, Id, Identifiable, Tagged};
+use sqlx::PgConnection;
+
+use crate::{Pairing, PairingKind};
+
+#[derive(Debug, Copy, Clone, Identifiable, Tagged)]
+#[souvenir(tag = "pair")]
+pub struct PairingRule {
+    #[souvenir(id)]
+    pub id: Id,
+    pub kind: PairingKind,
+    pub subject: Id,
+    pub partner: Id,
+}
+
+fn parse_kind(kind: &str) -> anyhow::Result<PairingKind> {
+    PairingKind::parse(kind).ok_or_else(|| anyhow::anyhow!("Unknown pairing kind `{kind}`"))
+}
+
+impl PairingRule {
+    pub fn new(kind: PairingKind, subject: Id, partner: Id) -> Self {
+        Self {
+            id: id!(PairingRule),
+            kind,
+            subject,
+            partner,
+        }
+    }
+
+    pub async fn find(id: Id, tx: &mut PgConnection) -> anyhow::Result<Self> {
+        let record = sqlx::query!(
+            r#"
+                SELECT kind, subject_id AS "subject: Id", partner_id AS "partner: Id"
+                    FROM pairing_rule WHERE id = $1;
+            "#,
+            id as Id,
+        )
+        .fetch_one(tx)
+        .await?;
+
+        Ok(Self {
+            id,
+            kind: parse_kind(&record.kind)?,
+            subject: record.subject,
+            partner: record.partner,
+        })
+    }
+
+    pub async fn all_rules(tx: &mut PgConnection) -> anyhow::Result<Vec<Self>> {
+        sqlx::query!(
+            r#"
+                SELECT id AS "id: Id", kind, subject_id AS "subject: Id", partner_id AS "partner: Id"
+                    FROM pairing_rule;
+            "#
+        )
+        .fetch_all(tx)
+        .await?
+        .into_iter()
+        .map(|record| {
+            Ok(Self {
+                id: record.id,
+                kind: parse_kind(&record.kind)?,
+                subject: record.subject,
+                partner: record.partner,
+            })
+        })
+        .collect()
+    }
+
+    pub async fn upsert(&self, tx: &mut PgConnection) -> anyhow::Result<()> {
+        sqlx::query!(
+            "
+                INSERT INTO pairing_rule (id, kind, subject_id, partner_id)
+                    VALUES ($1, $2, $3, $4)
+                    ON CONFLICT (id) DO UPDATE SET kind = $2, subject_id = $3, partner_id = $4;
+            ",
+            self.id as Id,
+            self.kind.as_str(),
+            self.subject as Id,
+            self.partner as Id,
+        )
+        .execute(tx)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete(self, tx: &mut PgConnection) -> anyhow::Result<()> {
+        sqlx::query!("DELETE FROM pairing_rule WHERE id = $1;", self.id as Id)
+            .execute(tx)
+            .await?;
+
+        Ok(())
+    }
+
+    pub fn pairing(&self) -> Pairing {
+        Pairing {
+            rule: self.id,
+            kind: self.kind,
+            subject: self.subject,
+            partner: self.partner,
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use souvenir::{id, Id, Identifiable, Tagged};
+use sqlx::PgConnection;
+
+use crate::RoleRequirement;
+
+/// A skill or responsibility subjects can hold, such as key-holder.
+#[derive(Debug, Clone, Identifiable, Tagged)]
+#[souvenir(tag = "role")]
+pub struct Role {
+    #[souvenir(id)]
+    pub id: Id,
+    pub name: String,
+}
+
+impl Role {
+    pub fn new(name: String) -> Self {
+        Self {
+            id: id!(Role),
+            name,
+        }
+    }
+
+    pub async fn find(id: Id, tx: &mut PgConnection) -> anyhow::Result<Self> {
+        Ok(sqlx::query_as!(
+            Role,
+            r#"SELECT id AS "id: Id", name FROM role WHERE id = $1;"#,
+            id as Id,
+        )
+        .fetch_one(tx)
+        .await?)
+    }
+
+    pub async fn find_by_name(name: &str, tx: &mut PgConnection) -> anyhow::Result<Option<Self>> {
+        Ok(sqlx::query_as!(
+            Role,
+            r#"SELECT id AS "id: Id", name FROM role WHERE name = $1;"#,
+            name,
+        )
+        .fetch_optional(tx)
+        .await?)
+    }
+
+    pub async fn all_roles(tx: &mut PgConnection) -> anyhow::Result<Vec<Self>> {
+        Ok(sqlx::query_as!(
+            Role,
+            r#"SELECT id AS "id: Id", name FROM role ORDER BY name;"#
+        )
+        .fetch_all(tx)
+        .await?)
+    }
+
+    pub async fn upsert(&self, tx: &mut PgConnection) -> anyhow::Result<()> {
+        sqlx::query!(
+            "
+                INSERT INTO role (id, name) VALUES ($1, $2)
+                    ON CONFLICT (id) DO UPDATE SET name = $2;
+            ",
+            self.id as Id,
+            self.name,
+        )
+        .execute(tx)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Deletes the role, along with every holding and requirement of it.
+    pub async fn delete(self, tx: &mut PgConnection) -> anyhow::Result<()> {
+        sqlx::query!("DELETE FROM role WHERE id = $1;", self.id as Id)
+            .execute(tx)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Roles held by every subject holding any.
+    pub async fn holders(tx: &mut PgConnection) -> anyhow::Result<HashMap<Id, Vec<Id>>> {
+        let mut holders: HashMap<Id, Vec<Id>> = HashMap::new();
+
+        sqlx::query!(
+            r#"SELECT subject_id AS "subject: Id", role_id AS "role: Id" FROM subject_role;"#
+        )
+        .fetch_all(tx)
+        .await?
+        .into_iter()
+        .for_each(|record| holders.entry(record.subject).or_default().push(record.role));
+
+        Ok(holders)
+    }
+
+    /// Role requirements of every slot declaring any.
+    pub async fn requirements(
+        tx: &mut PgConnection,
+    ) -> anyhow::Result<HashMap<Id, Vec<RoleRequirement>>> {
+        let mut requirements: HashMap<Id, Vec<RoleRequirement>> = HashMap::new();
+
+        sqlx::query!(
+            r#"
+                SELECT slot_id AS "slot: Id", role_id AS "role: Id", count
+                    FROM slot_role_requirement;
+            "#
+        )
+        .fetch_all(tx)
+        .await?
+        .into_iter()
+        .for_each(|record| {
+            requirements
+                .entry(record.slot)
+                .or_default()
+                .push(RoleRequirement {
+                    role: record.role,
+                    count: record.count as u32,
+                })
+        });
+
+        Ok(requirements)
+    }
+}
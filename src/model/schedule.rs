@@ -1,6 +1,10 @@
+use chrono::{DateTime, Utc};
+use serde_json::Value;
 use souvenir::{id, Id, Identifiable, Tagged};
 use sqlx::PgConnection;
 
+use crate::{Availability, Consideration, History, Options, ALGORITHM_VERSION};
+
 #[derive(Clone, Debug, Identifiable, Tagged)]
 #[souvenir(tag = "sch")]
 pub struct Schedule {
@@ -8,6 +12,23 @@ pub struct Schedule {
     #[souvenir(id)]
     pub id: Id,
     pub parent: Option<Id>,
+    /// Whether this schedule replaces its parent for the same week, rather
+    /// than following it.
+    pub revision: bool,
+    /// When the schedule was published. Published schedules are never
+    /// edited in place.
+    pub published_at: Option<DateTime<Utc>>,
+    /// Availability the schedule was generated from.
+    pub availability: Option<Id>,
+    /// Scoring strategy the schedule was generated with, if generated.
+    pub strategy: Option<String>,
+    /// Everything else needed to generate the schedule again: the scoring
+    /// parameters, solver and scheduler settings in effect.
+    pub parameters: Option<Value>,
+    /// Engine version that generated the schedule.
+    pub algorithm_version: Option<i32>,
+    pub created_at: DateTime<Utc>,
+    pub term: Option<Id>,
 }
 
 impl Schedule {
@@ -16,15 +37,75 @@ impl Schedule {
     }
 
     pub fn from(id: Id, parent: Option<Id>, name: Option<String>) -> Self {
-        Self { id, parent, name }
+        Self {
+            id,
+            parent,
+            name,
+            revision: false,
+            published_at: None,
+            availability: None,
+            strategy: None,
+            parameters: None,
+            algorithm_version: None,
+            created_at: Utc::now(),
+            term: None,
+        }
+    }
+
+    /// A new schedule replacing this one for the same week. It keeps the
+    /// availability and term, but is not generated until [`Self::generated`] says so.
+    pub fn revise(&self, name: Option<String>) -> Self {
+        Self {
+            id: id!(Schedule),
+            parent: Some(self.id),
+            name: name.or_else(|| self.name.clone()),
+            revision: true,
+            published_at: None,
+            availability: self.availability,
+            strategy: None,
+            parameters: None,
+            algorithm_version: None,
+            created_at: Utc::now(),
+            term: self.term,
+        }
+    }
+
+    /// Records that the schedule is generated from `availability` with
+    /// `options`. `settings` holds any other settings that shaped it.
+    pub fn generated(
+        &mut self,
+        availability: &Availability,
+        options: &Options,
+        settings: Value,
+    ) -> anyhow::Result<()> {
+        let mut parameters = serde_json::to_value(options)?;
+        parameters["settings"] = settings;
+
+        self.availability = Some(availability.id);
+        self.strategy = Some(options.scoring.strategy().to_string());
+        self.parameters = Some(parameters);
+        self.algorithm_version = Some(ALGORITHM_VERSION);
+
+        Ok(())
     }
 
     pub async fn resolve(id: Id, tx: &mut PgConnection) -> anyhow::Result<Self> {
         Ok(sqlx::query_as!(
             Schedule,
             r#"
-                SELECT id AS "id: Id", parent_id AS "parent: Id", name FROM schedule 
-                    WHERE id = $1;
+                SELECT
+                    id AS "id: Id",
+                    parent_id AS "parent: Id",
+                    name,
+                    revision,
+                    published_at,
+                    availability_id AS "availability: Id",
+                    strategy,
+                    parameters,
+                    algorithm_version,
+                    created_at,
+                    term_id AS "term: Id"
+                FROM schedule WHERE id = $1;
             "#,
             id as Id
         )
@@ -36,8 +117,19 @@ impl Schedule {
         Ok(sqlx::query_as!(
             Schedule,
             r#"
-                SELECT id AS "id: Id", parent_id AS "parent: Id", name FROM schedule 
-                    WHERE id = (SELECT schedule FROM parameters);
+                SELECT
+                    id AS "id: Id",
+                    parent_id AS "parent: Id",
+                    name,
+                    revision,
+                    published_at,
+                    availability_id AS "availability: Id",
+                    strategy,
+                    parameters,
+                    algorithm_version,
+                    created_at,
+                    term_id AS "term: Id"
+                FROM schedule WHERE id = (SELECT schedule FROM parameters);
             "#
         )
         .fetch_one(tx)
@@ -47,12 +139,22 @@ impl Schedule {
     pub async fn upsert(&mut self, tx: &mut PgConnection) -> anyhow::Result<()> {
         sqlx::query!(
             "
-            INSERT INTO schedule (id, parent_id, name) VALUES ($1, $2, $3)
+            INSERT INTO schedule (
+                id, parent_id, name, revision, availability_id, strategy, parameters,
+                algorithm_version, created_at, term_id
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
                 ON CONFLICT (id) DO UPDATE SET name = $3;
             ",
             self.id as Id,
             self.parent as Option<Id>,
-            self.name
+            self.name,
+            self.revision,
+            self.availability as Option<Id>,
+            self.strategy,
+            self.parameters,
+            self.algorithm_version,
+            self.created_at,
+            self.term as Option<Id>,
         )
         .execute(tx)
         .await?;
@@ -84,22 +186,7 @@ impl Schedule {
         subject: impl Identifiable,
         tx: &mut PgConnection,
     ) -> anyhow::Result<u32> {
-        let subject = subject.id();
-
-        let mut schedule = self.clone();
-        let mut count = 0;
-
-        loop {
-            count += schedule.count(subject, tx).await?;
-
-            if let Some(parent) = schedule.parent {
-                schedule = Schedule::resolve(parent, tx).await?;
-            } else {
-                break;
-            }
-        }
-
-        Ok(count)
+        Ok(self.history(tx).await?.total(subject.id()))
     }
 
     pub async fn last_scheduled(
@@ -107,23 +194,59 @@ impl Schedule {
         subject: impl Identifiable,
         tx: &mut PgConnection,
     ) -> anyhow::Result<Option<u64>> {
-        let subject = subject.id();
+        Ok(self.history(tx).await?.last_scheduled(subject.id()))
+    }
 
-        let mut schedule = self.clone();
-        let mut count = 0;
+    /// Loads shift counts for every subject across this schedule and all of
+    /// its ancestors in a single query. The schedule itself does not need to
+    /// be saved yet. A schedule replaced by a revision further down the
+    /// ancestry is skipped, and revising does not count as a week passing.
+    pub async fn history(&self, tx: &mut PgConnection) -> anyhow::Result<History> {
+        let mut history = History::default();
 
-        loop {
-            if schedule.count(subject, tx).await? > 0 {
-                return Ok(Some(count));
-            }
+        sqlx::query!(
+            r#"
+                WITH RECURSIVE ancestry (id, parent_id, depth, revision, counted, term_id) AS (
+                    SELECT $1::UUID, $2::UUID, 0, $3::BOOLEAN, TRUE, $4::UUID
+                    UNION ALL
+                    SELECT
+                        schedule.id,
+                        schedule.parent_id,
+                        ancestry.depth + CASE WHEN ancestry.revision THEN 0 ELSE 1 END,
+                        schedule.revision,
+                        NOT ancestry.revision,
+                        schedule.term_id
+                    FROM schedule
+                        INNER JOIN ancestry ON schedule.id = ancestry.parent_id
+                )
+                SELECT
+                    subject_id AS "subject: Id",
+                    depth AS "depth!",
+                    term_id IS DISTINCT FROM $4 AS "outside_term!",
+                    COUNT(*) AS "count!"
+                FROM schedule_assignment
+                    INNER JOIN ancestry ON schedule_id = ancestry.id
+                    WHERE counted
+                    GROUP BY subject_id, depth, term_id;
+            "#,
+            self.id as Id,
+            self.parent as Option<Id>,
+            self.revision,
+            self.term as Option<Id>,
+        )
+        .fetch_all(tx)
+        .await?
+        .into_iter()
+        .for_each(|record| {
+            history.insert(
+                record.subject,
+                record.depth as u64,
+                record.count as u32,
+                record.outside_term,
+            )
+        });
 
-            if let Some(parent) = schedule.parent {
-                schedule = Schedule::resolve(parent, tx).await?;
-                count += 1;
-            } else {
-                return Ok(None);
-            }
-        }
+        Ok(history)
     }
 
     pub async fn add(
@@ -147,6 +270,44 @@ impl Schedule {
         Ok(())
     }
 
+    pub async fn remove(
+        &self,
+        slot: impl Identifiable,
+        subject: impl Identifiable,
+        tx: &mut PgConnection,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            "
+                DELETE FROM schedule_assignment
+                    WHERE schedule_id = $1 AND slot_id = $2 AND subject_id = $3;
+            ",
+            self.id as Id,
+            slot.id() as Id,
+            subject.id() as Id,
+        )
+        .execute(tx)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Marks the schedule as published, if it is not already.
+    pub async fn publish(&mut self, tx: &mut PgConnection) -> Result<(), sqlx::Error> {
+        self.published_at = sqlx::query!(
+            "
+                UPDATE schedule SET published_at = COALESCE(published_at, now())
+                    WHERE id = $1
+                    RETURNING published_at;
+            ",
+            self.id as Id,
+        )
+        .fetch_one(tx)
+        .await?
+        .published_at;
+
+        Ok(())
+    }
+
     pub async fn get_slot(
         &self,
         slot: impl Identifiable,
@@ -166,4 +327,118 @@ impl Schedule {
         .map(|record| record.subject)
         .collect())
     }
+
+    /// Saves how a candidate was weighed for a slot during generation.
+    pub async fn record(
+        &self,
+        consideration: &Consideration,
+        tx: &mut PgConnection,
+    ) -> Result<(), sqlx::Error> {
+        let factors = &consideration.factors;
+
+        sqlx::query!(
+            "
+                INSERT INTO schedule_candidate (
+                    schedule_id, slot_id, subject_id,
+                    weeks_since, flexibility, total_shifted, shifts_current,
+                    weight, chosen, excluded
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10);
+            ",
+            self.id as Id,
+            consideration.slot as Id,
+            consideration.subject as Id,
+            factors.weeks_since.map(|weeks| weeks as i64),
+            factors.flexibility as i32,
+            factors.total_shifted,
+            factors.shifts_current as i32,
+            consideration.weight,
+            consideration.chosen,
+            consideration.excluded.map(|excluded| excluded.as_str()),
+        )
+        .execute(tx)
+        .await?;
+
+        Ok(())
+    }
+
+    /// The schedule of the week before this one, looking past any schedules
+    /// this one revises.
+    pub async fn predecessor(&self, tx: &mut PgConnection) -> anyhow::Result<Option<Self>> {
+        let mut current = self.clone();
+
+        while current.revision {
+            match current.parent {
+                Some(parent) => current = Self::resolve(parent, &mut *tx).await?,
+                None => return Ok(None),
+            }
+        }
+
+        match current.parent {
+            Some(parent) => Ok(Some(Self::resolve(parent, &mut *tx).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// `(slot, subject)` pairs pinned for schedules generated from this one.
+    pub async fn pins(&self, tx: &mut PgConnection) -> Result<Vec<(Id, Id)>, sqlx::Error> {
+        Ok(sqlx::query!(
+            r#"
+                SELECT slot_id AS "slot: Id", subject_id AS "subject: Id" FROM schedule_pin
+                    WHERE schedule_id = $1;
+            "#,
+            self.id as Id,
+        )
+        .fetch_all(tx)
+        .await?
+        .into_iter()
+        .map(|record| (record.slot, record.subject))
+        .collect())
+    }
+
+    /// Replaces the pins for schedules generated from this one.
+    pub async fn set_pins(
+        &self,
+        pins: &[(Id, Id)],
+        tx: &mut PgConnection,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "DELETE FROM schedule_pin WHERE schedule_id = $1;",
+            self.id as Id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        for &(slot, subject) in pins {
+            sqlx::query!(
+                "
+                    INSERT INTO schedule_pin (schedule_id, slot_id, subject_id)
+                        VALUES ($1, $2, $3);
+                ",
+                self.id as Id,
+                slot as Id,
+                subject as Id,
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Every `(slot, subject)` assignment in the schedule.
+    pub async fn assignments(&self, tx: &mut PgConnection) -> Result<Vec<(Id, Id)>, sqlx::Error> {
+        Ok(sqlx::query!(
+            r#"
+                SELECT slot_id AS "slot: Id", subject_id AS "subject: Id"
+                    FROM schedule_assignment
+                    WHERE schedule_id = $1;
+            "#,
+            self.id as Id,
+        )
+        .fetch_all(tx)
+        .await?
+        .into_iter()
+        .map(|record| (record.slot, record.subject))
+        .collect())
+    }
 }
@@ -1,24 +1,72 @@
+use serde::Serialize;
 use souvenir::{Id, Identifiable, Tagged};
 
 use sqlx::PgConnection;
 
+use crate::{RoleRequirement, SchedulerConfig};
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Identifiable, Tagged)]
 #[souvenir(tag = "slot")]
 pub struct Slot {
     #[souvenir(id)]
     pub id: Id,
     pub w2m_id: i32,
+    pub min_staff: Option<i32>,
+    pub max_staff: Option<i32>,
+}
+
+/// Resolved headcount bounds for a slot.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub struct Staffing {
+    pub min: u32,
+    pub max: u32,
 }
 
 impl Slot {
     pub fn new(id: Id, w2m_id: i32) -> Self {
-        Self { id, w2m_id }
+        Self {
+            id,
+            w2m_id,
+            min_staff: None,
+            max_staff: None,
+        }
+    }
+
+    /// Per-slot headcount, falling back to the configured defaults. The
+    /// maximum is never allowed to drop below the minimum.
+    pub fn staffing(&self, defaults: &SchedulerConfig) -> Staffing {
+        let min = self.min_staff.map_or(defaults.min_staff, |v| v as u32);
+        let max = self.max_staff.map_or(defaults.max_staff, |v| v as u32);
+
+        Staffing {
+            min,
+            max: max.max(min),
+        }
+    }
+
+    pub async fn find(id: Id, tx: &mut PgConnection) -> anyhow::Result<Self> {
+        let record = sqlx::query!(
+            r#"
+                SELECT id as "id: Id", w2m_id, min_staff, max_staff FROM slot
+                    WHERE id = $1;
+            "#,
+            id as Id,
+        )
+        .fetch_one(tx)
+        .await?;
+
+        Ok(Self {
+            id: record.id,
+            w2m_id: record.w2m_id.unwrap_or_default(),
+            min_staff: record.min_staff,
+            max_staff: record.max_staff,
+        })
     }
 
     pub async fn all_slots(tx: &mut PgConnection) -> anyhow::Result<Vec<Self>> {
         Ok(sqlx::query!(
             r#"
-                SELECT id as "id: Id", w2m_id FROM slot 
+                SELECT id as "id: Id", w2m_id, min_staff, max_staff FROM slot
                     ORDER BY w2m_id;
             "#
         )
@@ -28,7 +76,78 @@ impl Slot {
         .map(|record| Self {
             id: record.id,
             w2m_id: record.w2m_id.unwrap(),
+            min_staff: record.min_staff,
+            max_staff: record.max_staff,
         })
         .collect())
     }
+
+    pub async fn set_staffing(
+        &mut self,
+        min_staff: Option<i32>,
+        max_staff: Option<i32>,
+        tx: &mut PgConnection,
+    ) -> anyhow::Result<()> {
+        sqlx::query!(
+            "UPDATE slot SET min_staff = $2, max_staff = $3 WHERE id = $1;",
+            self.id as Id,
+            min_staff,
+            max_staff,
+        )
+        .execute(tx)
+        .await?;
+
+        self.min_staff = min_staff;
+        self.max_staff = max_staff;
+
+        Ok(())
+    }
+
+    pub async fn requirements(
+        &self,
+        tx: &mut PgConnection,
+    ) -> anyhow::Result<Vec<RoleRequirement>> {
+        Ok(sqlx::query!(
+            r#"
+                SELECT role_id AS "role: Id", count FROM slot_role_requirement
+                    WHERE slot_id = $1;
+            "#,
+            self.id as Id,
+        )
+        .fetch_all(tx)
+        .await?
+        .into_iter()
+        .map(|record| RoleRequirement {
+            role: record.role,
+            count: record.count as u32,
+        })
+        .collect())
+    }
+
+    /// Replaces the role requirements of the slot.
+    pub async fn set_requirements(
+        &self,
+        requirements: &[RoleRequirement],
+        tx: &mut PgConnection,
+    ) -> anyhow::Result<()> {
+        sqlx::query!(
+            "DELETE FROM slot_role_requirement WHERE slot_id = $1;",
+            self.id as Id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        for requirement in requirements {
+            sqlx::query!(
+                "INSERT INTO slot_role_requirement (slot_id, role_id, count) VALUES ($1, $2, $3);",
+                self.id as Id,
+                requirement.role as Id,
+                requirement.count as i32,
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        Ok(())
+    }
 }
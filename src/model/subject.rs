@@ -1,6 +1,9 @@
+use chrono::{DateTime, Utc};
 use souvenir::{Id, Identifiable, Tagged};
 use sqlx::PgConnection;
 
+use crate::Role;
+
 #[derive(Debug, Hash, PartialEq, Eq, Identifiable, Tagged)]
 #[souvenir(tag = "sub")]
 pub struct Subject {
@@ -8,6 +11,14 @@ pub struct Subject {
     pub id: Id,
     pub tag: String,
     pub name: Option<String>,
+    /// Overrides the configured cap on shifts per schedule.
+    pub max_shifts_per_week: Option<i32>,
+    /// Inactive subjects are left out of generation and statistics, even
+    /// when they still appear in an availability.
+    pub active: bool,
+    /// Unknown for subjects that predate lifecycle tracking.
+    pub joined_at: Option<DateTime<Utc>>,
+    pub left_at: Option<DateTime<Utc>>,
 }
 
 impl Subject {
@@ -20,6 +31,10 @@ impl Subject {
             id,
             tag: data.tag,
             name: data.name,
+            max_shifts_per_week: data.max_shifts_per_week,
+            active: data.active,
+            joined_at: data.joined_at,
+            left_at: data.left_at,
         })
     }
 
@@ -29,6 +44,7 @@ impl Subject {
         name: Option<&str>,
         tx: &mut PgConnection,
     ) -> Result<Self, sqlx::Error> {
+        #[allow(clippy::map_flatten)]
         let current_name = sqlx::query!("SELECT name FROM subject WHERE id = $1;", id as Id)
             .fetch_optional(&mut *tx)
             .await?
@@ -42,7 +58,7 @@ impl Subject {
                 INSERT INTO subject (id, tag, name)
                     VALUES ($1, $2, $3)
                     ON CONFLICT (tag) DO UPDATE SET tag = $2
-                    RETURNING id AS "id: Id", tag, name;
+                    RETURNING id AS "id: Id", tag, name, max_shifts_per_week, active, joined_at, left_at;
             "#,
             id as Id,
             tag,
@@ -54,21 +70,110 @@ impl Subject {
             id: result.id,
             tag: result.tag,
             name: result.name,
+            max_shifts_per_week: result.max_shifts_per_week,
+            active: result.active,
+            joined_at: result.joined_at,
+            left_at: result.left_at,
         })
     }
 
     pub async fn all_subjects(tx: &mut PgConnection) -> Result<Vec<Self>, sqlx::Error> {
-        Ok(
-            sqlx::query!(r#"SELECT id AS "id: Id", tag, name FROM subject;"#)
-                .fetch_all(tx)
-                .await?
-                .into_iter()
-                .map(|record| Subject {
-                    id: record.id,
-                    tag: record.tag,
-                    name: record.name,
-                })
-                .collect(),
+        Ok(sqlx::query!(
+            r#"
+                SELECT id AS "id: Id", tag, name, max_shifts_per_week, active, joined_at, left_at
+                    FROM subject;
+            "#
+        )
+        .fetch_all(tx)
+        .await?
+        .into_iter()
+        .map(|record| Subject {
+            id: record.id,
+            tag: record.tag,
+            name: record.name,
+            max_shifts_per_week: record.max_shifts_per_week,
+            active: record.active,
+            joined_at: record.joined_at,
+            left_at: record.left_at,
+        })
+        .collect())
+    }
+
+    pub async fn set_max_shifts(
+        &mut self,
+        max_shifts_per_week: Option<i32>,
+        tx: &mut PgConnection,
+    ) -> anyhow::Result<()> {
+        sqlx::query!(
+            "UPDATE subject SET max_shifts_per_week = $2 WHERE id = $1;",
+            self.id as Id,
+            max_shifts_per_week,
+        )
+        .execute(tx)
+        .await?;
+
+        self.max_shifts_per_week = max_shifts_per_week;
+
+        Ok(())
+    }
+
+    /// Marks the subject as having left, or as being back. Leaving records
+    /// when it happened; coming back clears it.
+    pub async fn set_active(&mut self, active: bool, tx: &mut PgConnection) -> anyhow::Result<()> {
+        let left_at = sqlx::query_scalar!(
+            r#"
+                UPDATE subject
+                    SET active = $2,
+                        left_at = CASE WHEN $2 THEN NULL ELSE COALESCE(left_at, now()) END
+                    WHERE id = $1
+                    RETURNING left_at;
+            "#,
+            self.id as Id,
+            active,
         )
+        .fetch_one(tx)
+        .await?;
+
+        self.active = active;
+        self.left_at = left_at;
+
+        Ok(())
+    }
+
+    pub async fn roles(&self, tx: &mut PgConnection) -> anyhow::Result<Vec<Role>> {
+        Ok(sqlx::query_as!(
+            Role,
+            r#"
+                SELECT id AS "id: Id", name FROM role
+                    INNER JOIN subject_role ON role_id = id
+                    WHERE subject_id = $1
+                    ORDER BY name;
+            "#,
+            self.id as Id,
+        )
+        .fetch_all(tx)
+        .await?)
+    }
+
+    /// Replaces the roles the subject holds.
+    pub async fn set_roles(&self, roles: &[Id], tx: &mut PgConnection) -> anyhow::Result<()> {
+        sqlx::query!(
+            "DELETE FROM subject_role WHERE subject_id = $1;",
+            self.id as Id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        for role in roles {
+            sqlx::query!(
+                "INSERT INTO subject_role (subject_id, role_id) VALUES ($1, $2);",
+                self.id as Id,
+                *role as Id,
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        Ok(())
     }
 }
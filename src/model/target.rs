@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+
+use souvenir::Id;
+use sqlx::PgConnection;
+
+/// Shifts a subject is contracted or volunteering for, per week or over a
+/// whole term. Unlike `max_shifts_per_week`, these are steered toward
+/// rather than enforced.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Targets {
+    pub weekly_min: Option<i32>,
+    pub weekly_max: Option<i32>,
+    pub term_min: Option<i32>,
+    pub term_max: Option<i32>,
+}
+
+impl Targets {
+    /// Targets of a subject, all unset if they have none.
+    pub async fn find(subject: Id, tx: &mut PgConnection) -> anyhow::Result<Self> {
+        Ok(sqlx::query_as!(
+            Targets,
+            "
+                SELECT weekly_min, weekly_max, term_min, term_max
+                    FROM subject_target WHERE subject_id = $1;
+            ",
+            subject as Id,
+        )
+        .fetch_optional(tx)
+        .await?
+        .unwrap_or_default())
+    }
+
+    /// Targets of every subject with any set.
+    pub async fn all(tx: &mut PgConnection) -> anyhow::Result<HashMap<Id, Self>> {
+        Ok(sqlx::query!(
+            r#"
+                SELECT subject_id AS "subject: Id", weekly_min, weekly_max, term_min, term_max
+                    FROM subject_target;
+            "#
+        )
+        .fetch_all(tx)
+        .await?
+        .into_iter()
+        .map(|record| {
+            let targets = Targets {
+                weekly_min: record.weekly_min,
+                weekly_max: record.weekly_max,
+                term_min: record.term_min,
+                term_max: record.term_max,
+            };
+
+            (record.subject, targets)
+        })
+        .collect())
+    }
+
+    /// Replaces the targets of a subject.
+    pub async fn set(&self, subject: Id, tx: &mut PgConnection) -> anyhow::Result<()> {
+        if *self == Self::default() {
+            sqlx::query!(
+                "DELETE FROM subject_target WHERE subject_id = $1;",
+                subject as Id
+            )
+            .execute(tx)
+            .await?;
+
+            return Ok(());
+        }
+
+        sqlx::query!(
+            "
+                INSERT INTO subject_target (subject_id, weekly_min, weekly_max, term_min, term_max)
+                    VALUES ($1, $2, $3, $4, $5)
+                    ON CONFLICT (subject_id) DO UPDATE
+                        SET weekly_min = $2, weekly_max = $3, term_min = $4, term_max = $5;
+            ",
+            subject as Id,
+            self.weekly_min,
+            self.weekly_max,
+            self.term_min,
+            self.term_max,
+        )
+        .execute(tx)
+        .await?;
+
+        Ok(())
+    }
+}
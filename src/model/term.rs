@@ -0,0 +1,151 @@
+use chrono::NaiveDate;
+use souvenir::{id, Id, Identifiable, Tagged};
+use sqlx::PgConnection;
+
+use crate::Schedule;
+
+/// A stretch of the calendar, such as an academic term, that groups the
+/// schedules generated for it.
+#[derive(Debug, Clone, Identifiable, Tagged)]
+#[souvenir(tag = "term")]
+pub struct Term {
+    #[souvenir(id)]
+    pub id: Id,
+    pub name: String,
+    pub starts_on: NaiveDate,
+    pub ends_on: NaiveDate,
+    /// Weight given to shifts from schedules outside the term when
+    /// generating schedules in it: 0 counts only shifts within the term, 1
+    /// counts every shift in full.
+    pub carry_over: f64,
+}
+
+impl Term {
+    pub fn new(name: String, starts_on: NaiveDate, ends_on: NaiveDate, carry_over: f64) -> Self {
+        Self {
+            id: id!(Term),
+            name,
+            starts_on,
+            ends_on,
+            carry_over,
+        }
+    }
+
+    pub async fn find(id: Id, tx: &mut PgConnection) -> anyhow::Result<Self> {
+        Ok(sqlx::query_as!(
+            Term,
+            r#"
+                SELECT id AS "id: Id", name, starts_on, ends_on, carry_over
+                    FROM term WHERE id = $1;
+            "#,
+            id as Id,
+        )
+        .fetch_one(tx)
+        .await?)
+    }
+
+    /// The term `date` falls in, if any. Should terms overlap, the one
+    /// starting last wins.
+    pub async fn containing(
+        date: NaiveDate,
+        tx: &mut PgConnection,
+    ) -> anyhow::Result<Option<Self>> {
+        Ok(sqlx::query_as!(
+            Term,
+            r#"
+                SELECT id AS "id: Id", name, starts_on, ends_on, carry_over
+                    FROM term WHERE starts_on <= $1 AND $1 <= ends_on
+                    ORDER BY starts_on DESC
+                    LIMIT 1;
+            "#,
+            date,
+        )
+        .fetch_optional(tx)
+        .await?)
+    }
+
+    pub async fn all_terms(tx: &mut PgConnection) -> anyhow::Result<Vec<Self>> {
+        Ok(sqlx::query_as!(
+            Term,
+            r#"
+                SELECT id AS "id: Id", name, starts_on, ends_on, carry_over
+                    FROM term ORDER BY starts_on;
+            "#
+        )
+        .fetch_all(tx)
+        .await?)
+    }
+
+    pub async fn upsert(&self, tx: &mut PgConnection) -> anyhow::Result<()> {
+        sqlx::query!(
+            "
+                INSERT INTO term (id, name, starts_on, ends_on, carry_over)
+                    VALUES ($1, $2, $3, $4, $5)
+                    ON CONFLICT (id) DO UPDATE
+                        SET name = $2, starts_on = $3, ends_on = $4, carry_over = $5;
+            ",
+            self.id as Id,
+            self.name,
+            self.starts_on,
+            self.ends_on,
+            self.carry_over,
+        )
+        .execute(tx)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Deletes the term. Its schedules are kept, outside of any term.
+    pub async fn delete(self, tx: &mut PgConnection) -> anyhow::Result<()> {
+        sqlx::query!("DELETE FROM term WHERE id = $1;", self.id as Id)
+            .execute(tx)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Assignments of the schedules in the term along the ancestry of
+    /// `schedule`, as `(schedule, slot, subject)`. As in
+    /// [`Schedule::history`], a schedule replaced by a revision further down
+    /// is skipped, so sibling drafts and abandoned branches are never counted.
+    pub async fn assignments(
+        &self,
+        schedule: &Schedule,
+        tx: &mut PgConnection,
+    ) -> anyhow::Result<Vec<(Id, Id, Id)>> {
+        Ok(sqlx::query!(
+            r#"
+                WITH RECURSIVE ancestry (id, parent_id, revision, counted, term_id) AS (
+                    SELECT $1::UUID, $2::UUID, $3::BOOLEAN, TRUE, $4::UUID
+                    UNION ALL
+                    SELECT
+                        schedule.id,
+                        schedule.parent_id,
+                        schedule.revision,
+                        NOT ancestry.revision,
+                        schedule.term_id
+                    FROM schedule
+                        INNER JOIN ancestry ON schedule.id = ancestry.parent_id
+                )
+                SELECT
+                    schedule_id AS "schedule: Id",
+                    slot_id AS "slot: Id",
+                    subject_id AS "subject: Id"
+                FROM schedule_assignment
+                    INNER JOIN ancestry ON schedule_id = ancestry.id
+                    WHERE counted AND ancestry.term_id = $5;
+            "#,
+            schedule.id as Id,
+            schedule.parent as Option<Id>,
+            schedule.revision,
+            schedule.term as Option<Id>,
+            self.id as Id,
+        )
+        .fetch_all(tx)
+        .await?
+        .into_iter()
+        .map(|record| (record.schedule, record.slot, record.subject))
+        .collect())
+    }
+}
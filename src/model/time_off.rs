@@ -0,0 +1,114 @@
+use chrono::NaiveDate;
+use souvenir::{id, Id, Identifiable, Tagged};
+use sqlx::PgConnection;
+
+/// Days a subject cannot work, such as for exams, trips or illness. Time
+/// off overrides whatever the subject marked in an availability.
+#[derive(Debug, Clone, Identifiable, Tagged)]
+#[souvenir(tag = "off")]
+pub struct TimeOff {
+    #[souvenir(id)]
+    pub id: Id,
+    pub subject: Id,
+    pub starts_on: NaiveDate,
+    /// Last day off, inclusive.
+    pub ends_on: NaiveDate,
+    pub reason: Option<String>,
+}
+
+impl TimeOff {
+    pub fn new(
+        subject: Id,
+        starts_on: NaiveDate,
+        ends_on: NaiveDate,
+        reason: Option<String>,
+    ) -> Self {
+        Self {
+            id: id!(TimeOff),
+            subject,
+            starts_on,
+            ends_on,
+            reason,
+        }
+    }
+
+    /// Whether `day` falls within the time off.
+    pub fn covers(&self, day: NaiveDate) -> bool {
+        self.starts_on <= day && day <= self.ends_on
+    }
+
+    pub async fn find(id: Id, tx: &mut PgConnection) -> anyhow::Result<Self> {
+        Ok(sqlx::query_as!(
+            TimeOff,
+            r#"
+                SELECT id AS "id: Id", subject_id AS "subject: Id", starts_on, ends_on, reason
+                    FROM time_off WHERE id = $1;
+            "#,
+            id as Id,
+        )
+        .fetch_one(tx)
+        .await?)
+    }
+
+    /// Time off of `subject` covering `day`, if any.
+    pub async fn covering(
+        subject: Id,
+        day: NaiveDate,
+        tx: &mut PgConnection,
+    ) -> anyhow::Result<Option<Self>> {
+        Ok(sqlx::query_as!(
+            TimeOff,
+            r#"
+                SELECT id AS "id: Id", subject_id AS "subject: Id", starts_on, ends_on, reason
+                    FROM time_off
+                    WHERE subject_id = $1 AND starts_on <= $2 AND $2 <= ends_on
+                    ORDER BY starts_on
+                    LIMIT 1;
+            "#,
+            subject as Id,
+            day,
+        )
+        .fetch_optional(tx)
+        .await?)
+    }
+
+    pub async fn all_time_off(tx: &mut PgConnection) -> anyhow::Result<Vec<Self>> {
+        Ok(sqlx::query_as!(
+            TimeOff,
+            r#"
+                SELECT id AS "id: Id", subject_id AS "subject: Id", starts_on, ends_on, reason
+                    FROM time_off ORDER BY starts_on;
+            "#
+        )
+        .fetch_all(tx)
+        .await?)
+    }
+
+    pub async fn upsert(&self, tx: &mut PgConnection) -> anyhow::Result<()> {
+        sqlx::query!(
+            "
+                INSERT INTO time_off (id, subject_id, starts_on, ends_on, reason)
+                    VALUES ($1, $2, $3, $4, $5)
+                    ON CONFLICT (id) DO UPDATE
+                        SET subject_id = $2, starts_on = $3, ends_on = $4, reason = $5;
+            ",
+            self.id as Id,
+            self.subject as Id,
+            self.starts_on,
+            self.ends_on,
+            self.reason,
+        )
+        .execute(tx)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete(self, tx: &mut PgConnection) -> anyhow::Result<()> {
+        sqlx::query!("DELETE FROM time_off WHERE id = $1;", self.id as Id)
+            .execute(tx)
+            .await?;
+
+        Ok(())
+    }
+}
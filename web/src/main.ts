@@ -9,6 +9,7 @@ import {
   GenerateResponse,
   Parameters,
   Slots,
+  Statistics,
 } from "./types.js";
 
 const app = express();
@@ -49,37 +50,61 @@ app.get("/", async (req, res) => {
 });
 
 app.get("/schedule/:id", async (req, res) => {
-  const slotsResult = await api<Slots>(`/slots`);
   const scheduleResult = await api<Schedule>(`/schedule/${req.params.id}`);
+  const statsResult = await api<Statistics>(
+    `/schedule/${req.params.id}/stats`,
+  );
 
-  if (!slotsResult.success) {
+  if (!scheduleResult.success) {
     return res.render("error", {
-      message: "Slots not found",
-      error: slotsResult.error,
+      message: "Schedule not found",
+      error: scheduleResult.error,
     });
   }
 
-  if (!scheduleResult.success) {
+  // The grid shows the week the schedule was generated for, falling back to
+  // the current availability for schedules that predate recording it.
+  let availabilityId = scheduleResult.data!.availability;
+
+  if (!availabilityId) {
+    const parametersResult = await api<Parameters>("/parameters");
+    availabilityId = parametersResult.data?.availability ?? null;
+  }
+
+  if (!availabilityId) {
     return res.render("error", {
-      message: "Schedule not found",
-      error: scheduleResult.error,
+      message: "Availability not found",
+      error: "No availability to lay the schedule out on",
+    });
+  }
+
+  const slotsResult = await api<Slots>(`/slots?availability=${availabilityId}`);
+
+  if (!slotsResult.success) {
+    return res.render("error", {
+      message: "Slots not found",
+      error: slotsResult.error,
     });
   }
 
   const slots = slotsResult.data!;
-  const { assignments } = scheduleResult.data!;
+  const { assignments, staffing } = scheduleResult.data!;
 
   res.render("schedule", {
     schedule: scheduleResult.data,
     scheduleId: req.params.id,
-    assignments: sortedData(assignments, slots).map((a) =>
-      a.data.map((k) => k.name ?? k.tag),
-    ),
+    stats: statsResult.success ? statsResult.data : null,
+    assignments: sortedData(assignments, slots).map((a) => ({
+      names: a.data.map((k) => k.name ?? k.tag),
+      understaffed: a.data.length < (staffing[a.id]?.min ?? 0),
+    })),
   });
 });
 
 app.get("/availability/:id", async (req, res) => {
-  const slotsResult = await api<Slots>(`/slots`);
+  const slotsResult = await api<Slots>(
+    `/slots?availability=${req.params.id}`,
+  );
   const availabilityResult = await api<Availability>(
     `/availability/${req.params.id}`,
   );
@@ -170,10 +195,9 @@ function sortedData(
   data: Record<string, { id: string; tag: string; name?: string }[]>,
   slots: { id: string; w2m_id: number }[],
 ): { id: string; data: { id: string; tag: string; name?: string }[] }[] {
-  const map: Record<string, number> = {};
-  slots.forEach((slot) => (map[slot.id] = slot.w2m_id));
-
-  return Object.entries(data)
-    .map((data) => ({ id: data[0], data: data[1] }))
-    .sort((a, b) => map[a.id] - map[b.id]);
+  // Every slot gets an entry, even with nobody in it, so the grid stays
+  // aligned and empty slots can still be highlighted.
+  return [...slots]
+    .sort((a, b) => a.w2m_id - b.w2m_id)
+    .map((slot) => ({ id: slot.id, data: data[slot.id] ?? [] }));
 }
@@ -8,7 +8,21 @@ export interface Schedule {
   id: string;
   name?: string;
   parent?: string;
+  revision: boolean;
+  published_at: string | null;
+  availability: string | null;
+  strategy: string | null;
+  parameters: Record<string, unknown> | null;
+  algorithm_version: number | null;
+  created_at: string;
+  term: string | null;
   assignments: Record<string, { id: string; tag: string; name?: string }[]>;
+  staffing: Record<string, Staffing>;
+}
+
+export interface Staffing {
+  min: number;
+  max: number;
 }
 
 export interface Availability {
@@ -21,12 +35,98 @@ export interface Availability {
 export interface GenerateRequest {
   name?: string | null;
   parent?: string | null;
+  scoring?: Record<string, unknown>;
+  solver?: "greedy" | "optimal";
+  improve?: boolean;
+  preview?: boolean;
+  pins?: Pin[];
+  seed?: number;
+  candidates?: number;
+  term?: string;
+  carry_over?: number;
+}
+
+export interface Pin {
+  slot: string;
+  subject: string;
+}
+
+export interface EditResponse {
+  id: string;
+  revised_from: string | null;
+  added: Pin[];
+  removed: Pin[];
 }
 
 export interface GenerateResponse {
   id: string;
   name?: string | null;
   parent?: string | null;
+  preview: boolean;
+  understaffed: Understaffed[];
+  unmet: Unmet[];
+  unsatisfied: Unsatisfied[];
+  deviations: Deviation[];
+  improvements: number;
+  slots: PlannedSlot[];
+  fairness: Fairness;
+  objective: number;
+  seed: number;
+  withdrawn: Withdrawn[];
+  alternatives?: GenerateResponse[];
+}
+
+export interface PlannedSlot {
+  slot: string;
+  w2m_id: number;
+  assignments: { subject: string; weight: number; pinned: boolean }[];
+  weight: number;
+}
+
+export interface Fairness {
+  double_shifts: number;
+  mean?: number | null;
+  stddev?: number | null;
+  nonzero_stddev?: number | null;
+}
+
+export interface Statistics extends Fairness {
+  id: string;
+  subjects: number;
+  gini?: number | null;
+  max_shifts?: number | null;
+  min_shifts?: number | null;
+  never_scheduled: string[];
+  histogram: { shifts: number; subjects: string[] }[];
+}
+
+export interface Understaffed {
+  slot: string;
+  w2m_id: number;
+  required: number;
+  assigned: number;
+  excluded: Excluded[];
+}
+
+export interface Excluded {
+  subject: string;
+  reason: "shift_cap" | "day_cap" | "rest_gap" | "must_pair" | "never_pair";
+}
+
+export interface Unmet {
+  slot: string;
+  w2m_id: number;
+  role: string;
+  required: number;
+  assigned: number;
+}
+
+export interface Unsatisfied {
+  rule: string;
+  kind: "must_pair" | "never_pair";
+  subject: string;
+  partner: string;
+  slots: string[];
 }
 
 export interface Parameters {
@@ -38,4 +138,64 @@ export interface Parameters {
 export type Slots = {
   id: string;
   w2m_id: number;
+  min_staff?: number | null;
+  max_staff?: number | null;
+  staffing: Staffing;
 }[];
+
+export interface Finding {
+  severity: "error" | "warning" | "info";
+  check: string;
+  slot: string | null;
+  subject: string | null;
+  message: string;
+}
+
+export interface ValidateResponse {
+  id: string;
+  availability: string;
+  valid: boolean;
+  findings: Finding[];
+}
+
+export interface Term {
+  id: string;
+  name: string;
+  starts_on: string;
+  ends_on: string;
+  carry_over: number;
+}
+
+export interface TermReport extends Term, Statistics {
+  schedules: number;
+}
+
+export interface Deviation {
+  subject: string;
+  min: number | null;
+  max: number | null;
+  assigned: number;
+  deviation: number;
+}
+
+export interface Targets {
+  weekly_min: number | null;
+  weekly_max: number | null;
+  term_min: number | null;
+  term_max: number | null;
+}
+
+export interface TimeOff {
+  id: string;
+  subject: string;
+  starts_on: string;
+  ends_on: string;
+  reason: string | null;
+}
+
+export interface Withdrawn {
+  slot: string;
+  subject: string;
+  time_off: string;
+  reason: string | null;
+}